@@ -1,7 +1,7 @@
 use tracing::{event, instrument, Level};
 use warp::filters::body::BodyDeserializeError;
 use warp::filters::cors::CorsForbidden;
-use warp::http::StatusCode;
+use warp::http::{header, StatusCode};
 use warp::reject::Reject;
 use warp::{Rejection, Reply};
 
@@ -9,16 +9,34 @@ use warp::{Rejection, Reply};
 pub enum Error {
     ParseError(std::num::ParseIntError),
     MissingParameters,
+    InvalidParameter(String),
+    InvalidTag(String),
+    NotFound,
     WrongPassword,
     CannotDecryptToken,
+    /// The session was logged out or its refresh token expired or was already used
+    InvalidSession,
+    /// The password reset token is unknown, expired or was already used
+    InvalidResetToken,
+    WeakPassword(String),
     Unauthorized,
+    Forbidden,
+    EmailNotVerified,
+    SelfVote,
+    ProfaneContent(String, Vec<String>),
     ArgonLibraryError(argon2::Error),
     DatabaseQueryError(sqlx::Error),
     MigrationError(sqlx::migrate::MigrateError),
+    ConfigError(String),
+    MailError(String),
     ReqwestAPIError(reqwest::Error),
     MiddlewareReqwestError(reqwest_middleware::Error),
     ClientError(APILayerError),
     ServerError(APILayerError),
+    /// The profanity filter is down, retry after the given number of seconds
+    ProfanityUnavailable(u64),
+    /// Rate limited or locked out, retry after the given number of seconds
+    TooManyRequests(u64),
 }
 
 #[derive(Debug, Clone)]
@@ -38,16 +56,37 @@ impl std::fmt::Display for Error {
         match self {
             Self::ParseError(ref err) => write!(f, "cannot parse parameter: {err}"),
             Self::MissingParameters => write!(f, "missing parameter"),
+            Self::InvalidParameter(err) => write!(f, "invalid parameter: {err}"),
+            Self::InvalidTag(err) => write!(f, "invalid tag: {err}"),
+            Self::NotFound => write!(f, "resource not found"),
             Self::WrongPassword => write!(f, "wrong password"),
             Self::CannotDecryptToken => write!(f, "cannot decrypt token"),
+            Self::InvalidSession => write!(f, "session expired or logged out"),
+            Self::InvalidResetToken => write!(f, "reset token is invalid or expired"),
+            Self::WeakPassword(reason) => write!(f, "password is too weak: {reason}"),
             Self::Unauthorized => write!(f, "no permission to change the underlying resource"),
+            Self::Forbidden => write!(f, "insufficient role for this action"),
+            Self::EmailNotVerified => write!(f, "email address is not verified"),
+            Self::SelfVote => write!(f, "cannot vote on your own content"),
+            Self::ProfaneContent(field, words) => {
+                write!(f, "{field} contains profanity: {}", words.join(", "))
+            }
             Self::ArgonLibraryError(_) => write!(f, "cannot verify password"),
             Self::DatabaseQueryError(_) => write!(f, "cannot update, invalid data"),
             Self::MigrationError(_) => write!(f, "cannot migrate database"),
+            Self::ConfigError(err) => write!(f, "invalid configuration: {err}"),
+            Self::MailError(err) => write!(f, "cannot send email: {err}"),
             Self::ReqwestAPIError(err) => write!(f, "cannot execute: {err}"),
             Self::MiddlewareReqwestError(err) => write!(f, "cannot execute: {err}"),
             Self::ClientError(err) => write!(f, "external client error: {err}"),
             Self::ServerError(err) => write!(f, "external server error: {err}"),
+            Self::ProfanityUnavailable(retry_after) => write!(
+                f,
+                "content cannot be checked right now, retry in {retry_after} seconds"
+            ),
+            Self::TooManyRequests(retry_after) => {
+                write!(f, "too many attempts, retry in {retry_after} seconds")
+            }
         }
     }
 }
@@ -58,9 +97,35 @@ impl Reject for APILayerError {}
 const DUPLICATE_KEY: u32 = 23505;
 
 #[instrument]
-pub async fn return_error(r: Rejection) -> Result<impl Reply, Rejection> {
+pub async fn return_error(r: Rejection) -> Result<warp::reply::Response, Rejection> {
+    if let Some(crate::Error::ProfanityUnavailable(retry_after)) = r.find() {
+        event!(Level::WARN, "profanity filter unavailable");
+        return Ok(warp::reply::with_header(
+            warp::reply::with_status(
+                crate::Error::ProfanityUnavailable(*retry_after).to_string(),
+                StatusCode::SERVICE_UNAVAILABLE,
+            ),
+            header::RETRY_AFTER,
+            retry_after.to_string(),
+        )
+        .into_response());
+    }
+
+    if let Some(crate::Error::TooManyRequests(retry_after)) = r.find() {
+        event!(Level::WARN, "request rate limited");
+        return Ok(warp::reply::with_header(
+            warp::reply::with_status(
+                crate::Error::TooManyRequests(*retry_after).to_string(),
+                StatusCode::TOO_MANY_REQUESTS,
+            ),
+            header::RETRY_AFTER,
+            retry_after.to_string(),
+        )
+        .into_response());
+    }
+
     #[allow(clippy::equatable_if_let)]
-    if let Some(crate::Error::DatabaseQueryError(e)) = r.find() {
+    let reply = if let Some(crate::Error::DatabaseQueryError(e)) = r.find() {
         event!(Level::ERROR, "database query error");
         match e {
             sqlx::Error::Database(err) => {
@@ -87,6 +152,30 @@ pub async fn return_error(r: Rejection) -> Result<impl Reply, Rejection> {
                 StatusCode::UNPROCESSABLE_ENTITY,
             )),
         }
+    } else if let Some(crate::Error::NotFound) = r.find() {
+        event!(Level::WARN, "requested resource was not found");
+        Ok(warp::reply::with_status(
+            "resource not found".to_string(),
+            StatusCode::NOT_FOUND,
+        ))
+    } else if let Some(crate::Error::Forbidden) = r.find() {
+        event!(Level::WARN, "account lacks the role for this action");
+        Ok(warp::reply::with_status(
+            "insufficient role for this action".to_string(),
+            StatusCode::FORBIDDEN,
+        ))
+    } else if let Some(crate::Error::EmailNotVerified) = r.find() {
+        event!(Level::WARN, "unverified account tried to post");
+        Ok(warp::reply::with_status(
+            "verify your email address before posting".to_string(),
+            StatusCode::FORBIDDEN,
+        ))
+    } else if let Some(crate::Error::SelfVote) = r.find() {
+        event!(Level::WARN, "account tried to vote on its own content");
+        Ok(warp::reply::with_status(
+            "cannot vote on your own content".to_string(),
+            StatusCode::FORBIDDEN,
+        ))
     } else if let Some(crate::Error::ReqwestAPIError(e)) = r.find() {
         event!(Level::ERROR, "{e}");
         Ok(warp::reply::with_status(
@@ -99,6 +188,21 @@ pub async fn return_error(r: Rejection) -> Result<impl Reply, Rejection> {
             "no presimmsion to change underlying resource".to_string(),
             StatusCode::UNAUTHORIZED,
         ))
+    } else if let Some(crate::Error::Unauthorized) = r.find() {
+        event!(
+            Level::WARN,
+            "account doesn't own the resource or token is invalid"
+        );
+        Ok(warp::reply::with_status(
+            "no permission to change the underlying resource".to_string(),
+            StatusCode::UNAUTHORIZED,
+        ))
+    } else if let Some(crate::Error::InvalidSession) = r.find() {
+        event!(Level::WARN, "session expired or logged out");
+        Ok(warp::reply::with_status(
+            "session expired or logged out".to_string(),
+            StatusCode::UNAUTHORIZED,
+        ))
     } else if let Some(crate::Error::WrongPassword) = r.find() {
         event!(Level::ERROR, "entered wrong password");
         Ok(warp::reply::with_status(
@@ -111,6 +215,12 @@ pub async fn return_error(r: Rejection) -> Result<impl Reply, Rejection> {
             "internal server error".to_string(),
             StatusCode::INTERNAL_SERVER_ERROR,
         ))
+    } else if let Some(crate::Error::MailError(e)) = r.find() {
+        event!(Level::ERROR, "{e}");
+        Ok(warp::reply::with_status(
+            "internal server error".to_string(),
+            StatusCode::INTERNAL_SERVER_ERROR,
+        ))
     } else if let Some(crate::Error::ClientError(e)) = r.find() {
         event!(Level::ERROR, "{e}");
         Ok(warp::reply::with_status(
@@ -146,5 +256,7 @@ pub async fn return_error(r: Rejection) -> Result<impl Reply, Rejection> {
             "route not found".to_string(),
             StatusCode::NOT_FOUND,
         ))
-    }
+    };
+
+    reply.map(Reply::into_response)
 }
@@ -4,6 +4,8 @@ use std::{collections::HashMap, net::SocketAddr};
 use tokio::sync::oneshot::{self, Sender};
 use warp::{http, reply::Reply, Filter};
 
+const BAD_WORD: &str = "shitty";
+
 #[derive(Clone, Debug)]
 pub struct MockServer {
     socket: SocketAddr,
@@ -19,40 +21,43 @@ impl MockServer {
         Self { socket: bind_addr }
     }
 
+    /// Mimics the bad words API: content is lowercased, every `shitty` is censored and
+    /// `censored_content` is empty when nothing was found.
     #[allow(clippy::unused_async)]
     async fn check_profanity((): (), content: Bytes) -> Result<impl warp::Reply, warp::Rejection> {
-        let content = String::from_utf8(content.to_vec()).expect("invalid UTF-8");
-        if content.contains("shitty") {
-            Ok(warp::reply::with_status(
-                warp::reply::json(&json!({
-                    "bad_words_list": [
-                    {
-                        "deviations": 0,
-                        "end": 16,
-                        "info": 2,
-                        "original": "shitty",
-                        "replacedLen": 6,
-                        "start": 10,
-                        "word": "shitty"
-                    }
-                    ],
-                    "bad_words_total": 1,
-                    "censored_content": "this is a ****** sentence",
-                    "content": "this is a shitty sentence"
-                })),
-                http::StatusCode::OK,
-            ))
+        let content = String::from_utf8(content.to_vec())
+            .expect("invalid UTF-8")
+            .to_lowercase();
+        let bad_words_list: Vec<_> = content
+            .match_indices(BAD_WORD)
+            .map(|(start, word)| {
+                let start = content[..start].chars().count();
+                json!({
+                    "deviations": 0,
+                    "end": start + word.len(),
+                    "info": 2,
+                    "original": word,
+                    "replacedLen": word.len(),
+                    "start": start,
+                    "word": word
+                })
+            })
+            .collect();
+        let censored_content = if bad_words_list.is_empty() {
+            String::new()
         } else {
-            Ok(warp::reply::with_status(
-                warp::reply::json(&json!({
-                    "bad_words_list": [],
-                    "bad_words_total": 0,
-                    "censored_content": "",
-                    "content": "this is a sentence"
-                })),
-                http::StatusCode::OK,
-            ))
-        }
+            content.replace(BAD_WORD, &"*".repeat(BAD_WORD.len()))
+        };
+
+        Ok(warp::reply::with_status(
+            warp::reply::json(&json!({
+                "bad_words_total": bad_words_list.len(),
+                "bad_words_list": bad_words_list,
+                "censored_content": censored_content,
+                "content": content
+            })),
+            http::StatusCode::OK,
+        ))
     }
 
     #[allow(clippy::unused_self)]
@@ -0,0 +1,6 @@
+DROP INDEX IF EXISTS answers_search_idx;
+ALTER TABLE answers
+DROP COLUMN search;
+DROP INDEX IF EXISTS questions_search_idx;
+ALTER TABLE questions
+DROP COLUMN search;
@@ -0,0 +1,12 @@
+ALTER TABLE questions
+ADD COLUMN search tsvector GENERATED ALWAYS AS (
+  setweight(to_tsvector('english', coalesce(title, '')), 'A') ||
+  setweight(to_tsvector('english', coalesce(content, '')), 'B')
+) STORED;
+CREATE INDEX IF NOT EXISTS questions_search_idx ON questions USING GIN (search);
+
+ALTER TABLE answers
+ADD COLUMN search tsvector GENERATED ALWAYS AS (
+  to_tsvector('english', coalesce(content, ''))
+) STORED;
+CREATE INDEX IF NOT EXISTS answers_search_idx ON answers USING GIN (search);
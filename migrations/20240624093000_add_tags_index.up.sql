@@ -0,0 +1,8 @@
+UPDATE questions
+SET tags = ARRAY(
+  SELECT DISTINCT lower(trim(tag))
+  FROM unnest(tags) AS tag
+  WHERE trim(tag) <> ''
+)
+WHERE tags IS NOT NULL;
+CREATE INDEX IF NOT EXISTS questions_tags_idx ON questions USING GIN (tags);
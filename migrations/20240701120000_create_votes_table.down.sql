@@ -0,0 +1,5 @@
+ALTER TABLE answers
+DROP COLUMN score;
+ALTER TABLE questions
+DROP COLUMN score;
+DROP TABLE IF EXISTS votes;
@@ -0,0 +1,18 @@
+CREATE TABLE IF NOT EXISTS votes (
+  id serial PRIMARY KEY,
+  account_id integer NOT NULL,
+  question_id integer REFERENCES questions ON DELETE CASCADE,
+  answer_id integer REFERENCES answers ON DELETE CASCADE,
+  value smallint NOT NULL CHECK (value IN (-1, 1)),
+  created_on TIMESTAMP NOT NULL DEFAULT NOW(),
+  CHECK ((question_id IS NULL) <> (answer_id IS NULL))
+);
+CREATE UNIQUE INDEX IF NOT EXISTS votes_question_idx
+  ON votes (account_id, question_id) WHERE question_id IS NOT NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS votes_answer_idx
+  ON votes (account_id, answer_id) WHERE answer_id IS NOT NULL;
+
+ALTER TABLE questions
+ADD COLUMN score integer NOT NULL DEFAULT 0;
+ALTER TABLE answers
+ADD COLUMN score integer NOT NULL DEFAULT 0;
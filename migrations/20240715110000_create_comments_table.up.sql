@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS comments (
+  id serial PRIMARY KEY,
+  content TEXT NOT NULL,
+  account_id integer NOT NULL,
+  question_id integer REFERENCES questions ON DELETE CASCADE,
+  answer_id integer REFERENCES answers ON DELETE CASCADE,
+  parent_id integer REFERENCES comments ON DELETE CASCADE,
+  created_on TIMESTAMP NOT NULL DEFAULT NOW(),
+  CHECK ((question_id IS NULL) <> (answer_id IS NULL))
+);
+CREATE INDEX IF NOT EXISTS comments_question_idx ON comments (question_id);
+CREATE INDEX IF NOT EXISTS comments_answer_idx ON comments (answer_id);
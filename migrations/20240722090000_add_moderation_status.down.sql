@@ -0,0 +1,10 @@
+DROP INDEX IF EXISTS answers_pending_review_idx;
+DROP INDEX IF EXISTS questions_pending_review_idx;
+
+ALTER TABLE answers
+DROP COLUMN IF EXISTS moderation_status;
+
+ALTER TABLE questions
+DROP COLUMN IF EXISTS moderation_status;
+
+DROP TYPE IF EXISTS moderation_status;
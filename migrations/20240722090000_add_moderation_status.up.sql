@@ -0,0 +1,13 @@
+CREATE TYPE moderation_status AS ENUM ('approved', 'pending_review');
+
+ALTER TABLE questions
+ADD COLUMN IF NOT EXISTS moderation_status moderation_status NOT NULL DEFAULT 'approved';
+
+ALTER TABLE answers
+ADD COLUMN IF NOT EXISTS moderation_status moderation_status NOT NULL DEFAULT 'approved';
+
+CREATE INDEX IF NOT EXISTS questions_pending_review_idx ON questions (created_on)
+WHERE moderation_status = 'pending_review';
+
+CREATE INDEX IF NOT EXISTS answers_pending_review_idx ON answers (created_on)
+WHERE moderation_status = 'pending_review';
@@ -0,0 +1,12 @@
+DROP TABLE IF EXISTS audit_log;
+DROP TYPE IF EXISTS audit_target;
+DROP TYPE IF EXISTS audit_action;
+
+-- Postgres cannot drop enum values, so hidden posts go back to the review queue
+UPDATE questions SET moderation_status = 'pending_review' WHERE moderation_status = 'hidden';
+UPDATE answers SET moderation_status = 'pending_review' WHERE moderation_status = 'hidden';
+
+ALTER TABLE accounts
+DROP COLUMN IF EXISTS role;
+
+DROP TYPE IF EXISTS account_role;
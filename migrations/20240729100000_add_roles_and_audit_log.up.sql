@@ -0,0 +1,21 @@
+CREATE TYPE account_role AS ENUM ('user', 'moderator', 'admin');
+
+ALTER TABLE accounts
+ADD COLUMN IF NOT EXISTS role account_role NOT NULL DEFAULT 'user';
+
+ALTER TYPE moderation_status ADD VALUE IF NOT EXISTS 'hidden';
+
+CREATE TYPE audit_action AS ENUM ('approve', 'hide', 'delete', 'set_role');
+CREATE TYPE audit_target AS ENUM ('question', 'answer', 'account');
+
+-- Targets are not foreign keys so entries survive the deletion they record
+CREATE TABLE IF NOT EXISTS audit_log (
+  id serial PRIMARY KEY,
+  account_id integer NOT NULL,
+  action audit_action NOT NULL,
+  target audit_target NOT NULL,
+  target_id integer NOT NULL,
+  details TEXT,
+  created_on TIMESTAMP NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS audit_log_created_on_idx ON audit_log (created_on);
@@ -0,0 +1,17 @@
+CREATE TYPE flag_reason AS ENUM ('spam', 'abuse', 'off_topic', 'other');
+
+CREATE TABLE IF NOT EXISTS flags (
+  id serial PRIMARY KEY,
+  account_id integer NOT NULL,
+  question_id integer REFERENCES questions ON DELETE CASCADE,
+  answer_id integer REFERENCES answers ON DELETE CASCADE,
+  reason flag_reason NOT NULL,
+  created_on TIMESTAMP NOT NULL DEFAULT NOW(),
+  resolved_on TIMESTAMP,
+  CHECK ((question_id IS NULL) <> (answer_id IS NULL))
+);
+-- One open flag per account and post, resolved flags are kept for the record
+CREATE UNIQUE INDEX IF NOT EXISTS flags_question_idx
+  ON flags (account_id, question_id) WHERE question_id IS NOT NULL AND resolved_on IS NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS flags_answer_idx
+  ON flags (account_id, answer_id) WHERE answer_id IS NOT NULL AND resolved_on IS NULL;
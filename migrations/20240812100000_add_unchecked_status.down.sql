@@ -0,0 +1,3 @@
+-- Postgres cannot drop enum values, so unchecked posts go to the review queue
+UPDATE questions SET moderation_status = 'pending_review' WHERE moderation_status = 'unchecked';
+UPDATE answers SET moderation_status = 'pending_review' WHERE moderation_status = 'unchecked';
@@ -0,0 +1 @@
+ALTER TYPE moderation_status ADD VALUE IF NOT EXISTS 'unchecked';
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS answers_unchecked_idx;
+DROP INDEX IF EXISTS questions_unchecked_idx;
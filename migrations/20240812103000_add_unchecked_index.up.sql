@@ -0,0 +1,6 @@
+-- Separate from adding the enum value, which cannot be used in the same transaction
+CREATE INDEX IF NOT EXISTS questions_unchecked_idx ON questions (created_on)
+WHERE moderation_status = 'unchecked';
+
+CREATE INDEX IF NOT EXISTS answers_unchecked_idx ON answers (created_on)
+WHERE moderation_status = 'unchecked';
@@ -0,0 +1,10 @@
+-- Refresh tokens are stored as SHA-256 hashes, a leaked table can't be used to log in
+CREATE TABLE IF NOT EXISTS sessions (
+  id serial PRIMARY KEY,
+  account_id integer NOT NULL,
+  refresh_token_hash BYTEA NOT NULL UNIQUE,
+  created_on TIMESTAMP NOT NULL DEFAULT NOW(),
+  expires_on TIMESTAMP NOT NULL,
+  revoked_on TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS sessions_account_idx ON sessions (account_id) WHERE revoked_on IS NULL;
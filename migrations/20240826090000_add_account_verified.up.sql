@@ -0,0 +1,6 @@
+-- Accounts created before verification existed count as verified
+ALTER TABLE accounts
+ADD COLUMN IF NOT EXISTS verified BOOLEAN NOT NULL DEFAULT TRUE;
+
+ALTER TABLE accounts
+ALTER COLUMN verified SET DEFAULT FALSE;
@@ -0,0 +1,11 @@
+-- Reset tokens are stored as SHA-256 hashes and can be used once
+CREATE TABLE IF NOT EXISTS password_resets (
+  id serial PRIMARY KEY,
+  account_id integer NOT NULL,
+  token_hash BYTEA NOT NULL UNIQUE,
+  created_on TIMESTAMP NOT NULL DEFAULT NOW(),
+  expires_on TIMESTAMP NOT NULL,
+  used_on TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS password_resets_account_idx ON password_resets (account_id)
+WHERE used_on IS NULL;
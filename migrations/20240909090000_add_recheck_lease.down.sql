@@ -0,0 +1,5 @@
+ALTER TABLE answers
+DROP COLUMN IF EXISTS recheck_claimed_until;
+
+ALTER TABLE questions
+DROP COLUMN IF EXISTS recheck_claimed_until;
@@ -0,0 +1,6 @@
+-- Set while a server instance rechecks the post, so other instances skip it until then
+ALTER TABLE questions
+ADD COLUMN IF NOT EXISTS recheck_claimed_until TIMESTAMP;
+
+ALTER TABLE answers
+ADD COLUMN IF NOT EXISTS recheck_claimed_until TIMESTAMP;
@@ -1,5 +1,27 @@
-use clap::Parser;
-use std::env;
+use crate::keyring::Keyring;
+use crate::types::{Argon2Params, ModerationPolicy, PasswordPolicy};
+use clap::{Parser, ValueEnum};
+use std::{env, path::PathBuf};
+
+/// Backend used to check user content for profanity
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ProfanityBackend {
+    /// The hosted APILayer bad words API
+    #[default]
+    ApiLayer,
+    /// A local wordlist, no network calls
+    Wordlist,
+}
+
+/// Backend used to send emails
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MailerBackend {
+    /// Write emails to a directory or the log, nothing is sent
+    #[default]
+    Log,
+    /// Send emails through an SMTP relay
+    Smtp,
+}
 
 /// Eroteme web service API
 #[derive(Parser, Debug, PartialEq, Eq)]
@@ -26,6 +48,89 @@ pub struct Config {
     /// Database name
     #[clap(long, default_value = "eroteme")]
     pub db_name: String,
+    /// Profanity filter backend (api-layer or wordlist)
+    #[clap(long, value_enum, default_value = "api-layer")]
+    pub profanity_filter: ProfanityBackend,
+    /// Path to the wordlist used by the wordlist profanity filter, one word per line
+    #[clap(long)]
+    pub wordlist: Option<PathBuf>,
+    /// Base URL of the APILayer API
+    #[clap(long, default_value = "https://api.apilayer.com")]
+    pub api_layer_url: String,
+    /// Number of profanity check results kept in memory, 0 disables the cache
+    #[clap(long, default_value = "1024")]
+    pub profanity_cache_size: usize,
+    /// Consecutive APILayer failures after which it isn't called for a while
+    #[clap(long, default_value = "5")]
+    pub breaker_failure_threshold: u32,
+    /// Seconds to wait before calling APILayer again after too many failures
+    #[clap(long, default_value = "30")]
+    pub breaker_open_secs: u64,
+    /// Seconds between rechecks of posts written while the profanity filter was
+    /// unavailable, 0 disables them
+    #[clap(long, default_value = "60")]
+    pub recheck_interval_secs: u64,
+    /// Number of questions and answers rechecked at a time
+    #[clap(long, default_value = "20")]
+    pub recheck_batch_size: i64,
+    /// Base URL the API is reached at, used for links in emails
+    #[clap(long, default_value = "http://localhost:8080")]
+    pub public_url: String,
+    /// Email backend (log or smtp)
+    #[clap(long, value_enum, default_value = "log")]
+    pub mailer: MailerBackend,
+    /// Directory the log mailer writes emails to, they are logged if not set
+    #[clap(long)]
+    pub mail_dir: Option<PathBuf>,
+    /// Sender address of emails
+    #[clap(long, default_value = "Eroteme <noreply@localhost>")]
+    pub mail_from: String,
+    /// SMTP relay hostname
+    #[clap(long, default_value = "localhost")]
+    pub smtp_host: String,
+    /// SMTP relay port
+    #[clap(long, default_value = "587")]
+    pub smtp_port: u16,
+    /// Only let accounts with a verified email address post
+    #[clap(long)]
+    pub require_verified: bool,
+    /// Seconds in which the login and registration rate limits are counted
+    #[clap(long, default_value = "60")]
+    pub rate_limit_window_secs: u64,
+    /// Login or registration attempts per client address in a window, 0 disables the limit
+    #[clap(long, default_value = "30")]
+    pub rate_limit_per_ip: u32,
+    /// Login or registration attempts per email address in a window, 0 disables the limit
+    #[clap(long, default_value = "10")]
+    pub rate_limit_per_email: u32,
+    /// Consecutive wrong passwords after which an email is locked out, 0 disables lockouts
+    #[clap(long, default_value = "5")]
+    pub lockout_threshold: u32,
+    /// Seconds of the first lockout, doubled with every further wrong password
+    #[clap(long, default_value = "60")]
+    pub lockout_base_secs: u64,
+    /// Longest lockout in seconds
+    #[clap(long, default_value = "3600")]
+    pub lockout_max_secs: u64,
+    /// Read the client address from `X-Forwarded-For`, only set behind a reverse proxy
+    #[clap(long)]
+    pub trust_forwarded_for: bool,
+    /// SMTP username and password, read from `SMTP_USERNAME` and `SMTP_PASSWORD`
+    #[clap(skip)]
+    pub smtp_credentials: Option<(String, String)>,
+    /// APILayer API key, read from `BAD_WORDS_API_KEY`
+    #[clap(skip)]
+    pub bad_words_api_key: String,
+    /// Keys for access tokens, read from `PASETO_KEY`, `PASETO_KEY_ID` and
+    /// `PASETO_PREVIOUS_KEYS`
+    #[clap(skip)]
+    pub keyring: Keyring,
+    #[clap(flatten)]
+    pub moderation: ModerationPolicy,
+    #[clap(flatten)]
+    pub argon2: Argon2Params,
+    #[clap(flatten)]
+    pub password: PasswordPolicy,
 }
 
 impl Config {
@@ -39,12 +144,159 @@ impl Config {
     pub fn new() -> Result<Self, handle_errors::Error> {
         let config = Self::parse();
 
-        assert!(
-            env::var("BAD_WORDS_API_KEY").is_ok(),
-            "missing BadWords API key"
-        );
+        let profanity_filter = env_value_enum("PROFANITY_FILTER", config.profanity_filter)?;
+        let wordlist = env::var("PROFANITY_WORDLIST")
+            .ok()
+            .map(PathBuf::from)
+            .or(config.wordlist);
+        let api_layer_url = env::var("API_LAYER_URL").unwrap_or(config.api_layer_url);
+        let bad_words_api_key = env::var("BAD_WORDS_API_KEY").unwrap_or_default();
+        let profanity_cache_size = env::var("PROFANITY_CACHE_SIZE")
+            .ok()
+            .map_or(Ok(config.profanity_cache_size), |val| val.parse::<usize>())
+            .map_err(handle_errors::Error::ParseError)?;
+        let breaker_failure_threshold = env::var("BREAKER_FAILURE_THRESHOLD")
+            .ok()
+            .map_or(Ok(config.breaker_failure_threshold), |val| {
+                val.parse::<u32>()
+            })
+            .map_err(handle_errors::Error::ParseError)?;
+        if breaker_failure_threshold < 1 {
+            return Err(handle_errors::Error::ConfigError(
+                "breaker failure threshold must be at least 1".to_owned(),
+            ));
+        }
+        let breaker_open_secs = env::var("BREAKER_OPEN_SECS")
+            .ok()
+            .map_or(Ok(config.breaker_open_secs), |val| val.parse::<u64>())
+            .map_err(handle_errors::Error::ParseError)?;
+        let recheck_interval_secs = env::var("RECHECK_INTERVAL_SECS")
+            .ok()
+            .map_or(Ok(config.recheck_interval_secs), |val| val.parse::<u64>())
+            .map_err(handle_errors::Error::ParseError)?;
+        let recheck_batch_size = env::var("RECHECK_BATCH_SIZE")
+            .ok()
+            .map_or(Ok(config.recheck_batch_size), |val| val.parse::<i64>())
+            .map_err(handle_errors::Error::ParseError)?;
+        if recheck_batch_size < 1 {
+            return Err(handle_errors::Error::ConfigError(
+                "recheck batch size must be at least 1".to_owned(),
+            ));
+        }
+
+        let flag_threshold = env::var("FLAG_THRESHOLD")
+            .ok()
+            .map_or(Ok(config.moderation.flag_threshold), |val| {
+                val.parse::<i64>()
+            })
+            .map_err(handle_errors::Error::ParseError)?;
+        if flag_threshold < 1 {
+            return Err(handle_errors::Error::ConfigError(
+                "flag threshold must be at least 1".to_owned(),
+            ));
+        }
+
+        match profanity_filter {
+            ProfanityBackend::ApiLayer => {
+                assert!(!bad_words_api_key.is_empty(), "missing BadWords API key")
+            }
+            ProfanityBackend::Wordlist => {
+                assert!(wordlist.is_some(), "missing profanity wordlist");
+            }
+        }
+
+        let keyring = Keyring::from_env()?;
+
+        let public_url = env::var("PUBLIC_URL").unwrap_or(config.public_url);
+        let mailer = env_value_enum("MAILER", config.mailer)?;
+        let mail_dir = env::var("MAIL_DIR")
+            .ok()
+            .map(PathBuf::from)
+            .or(config.mail_dir);
+        let mail_from = env::var("MAIL_FROM").unwrap_or(config.mail_from);
+        let smtp_host = env::var("SMTP_HOST").unwrap_or(config.smtp_host);
+        let smtp_port = env::var("SMTP_PORT")
+            .ok()
+            .map_or(Ok(config.smtp_port), |val| val.parse::<u16>())
+            .map_err(handle_errors::Error::ParseError)?;
+        let smtp_credentials = env::var("SMTP_USERNAME")
+            .ok()
+            .zip(env::var("SMTP_PASSWORD").ok());
+        let require_verified = env::var("REQUIRE_VERIFIED")
+            .ok()
+            .map_or(Ok(config.require_verified), |val| val.parse::<bool>())
+            .map_err(|_| {
+                handle_errors::Error::ConfigError(
+                    "REQUIRE_VERIFIED must be true or false".to_owned(),
+                )
+            })?;
+
+        let rate_limit_window_secs = env::var("RATE_LIMIT_WINDOW_SECS")
+            .ok()
+            .map_or(Ok(config.rate_limit_window_secs), |val| val.parse::<u64>())
+            .map_err(handle_errors::Error::ParseError)?;
+        let rate_limit_per_ip = env::var("RATE_LIMIT_PER_IP")
+            .ok()
+            .map_or(Ok(config.rate_limit_per_ip), |val| val.parse::<u32>())
+            .map_err(handle_errors::Error::ParseError)?;
+        let rate_limit_per_email = env::var("RATE_LIMIT_PER_EMAIL")
+            .ok()
+            .map_or(Ok(config.rate_limit_per_email), |val| val.parse::<u32>())
+            .map_err(handle_errors::Error::ParseError)?;
+        let lockout_threshold = env::var("LOCKOUT_THRESHOLD")
+            .ok()
+            .map_or(Ok(config.lockout_threshold), |val| val.parse::<u32>())
+            .map_err(handle_errors::Error::ParseError)?;
+        let lockout_base_secs = env::var("LOCKOUT_BASE_SECS")
+            .ok()
+            .map_or(Ok(config.lockout_base_secs), |val| val.parse::<u64>())
+            .map_err(handle_errors::Error::ParseError)?;
+        let lockout_max_secs = env::var("LOCKOUT_MAX_SECS")
+            .ok()
+            .map_or(Ok(config.lockout_max_secs), |val| val.parse::<u64>())
+            .map_err(handle_errors::Error::ParseError)?;
+        if lockout_max_secs < lockout_base_secs {
+            return Err(handle_errors::Error::ConfigError(
+                "lockout max secs must be at least lockout base secs".to_owned(),
+            ));
+        }
+        let trust_forwarded_for = env::var("TRUST_FORWARDED_FOR")
+            .ok()
+            .map_or(Ok(config.trust_forwarded_for), |val| val.parse::<bool>())
+            .map_err(|_| {
+                handle_errors::Error::ConfigError(
+                    "TRUST_FORWARDED_FOR must be true or false".to_owned(),
+                )
+            })?;
 
-        assert!(env::var("PASETO_KEY").is_ok(), "missing Paseto key");
+        let argon2 = Argon2Params {
+            memory_kib: env::var("ARGON2_MEMORY_KIB")
+                .ok()
+                .map_or(Ok(config.argon2.memory_kib), |val| val.parse::<u32>())
+                .map_err(handle_errors::Error::ParseError)?,
+            iterations: env::var("ARGON2_ITERATIONS")
+                .ok()
+                .map_or(Ok(config.argon2.iterations), |val| val.parse::<u32>())
+                .map_err(handle_errors::Error::ParseError)?,
+            parallelism: env::var("ARGON2_PARALLELISM")
+                .ok()
+                .map_or(Ok(config.argon2.parallelism), |val| val.parse::<u32>())
+                .map_err(handle_errors::Error::ParseError)?,
+        };
+        argon2.validate()?;
+        let password_min_length = env::var("PASSWORD_MIN_LENGTH")
+            .ok()
+            .map_or(Ok(config.password.min_length), |val| val.parse::<usize>())
+            .map_err(handle_errors::Error::ParseError)?;
+        if password_min_length < 1 {
+            return Err(handle_errors::Error::ConfigError(
+                "password min length must be at least 1".to_owned(),
+            ));
+        }
+        let breached_passwords = env::var("BREACHED_PASSWORDS")
+            .ok()
+            .map(PathBuf::from)
+            .or(config.password.breached_passwords);
 
         let port = env::var("PORT")
             .ok()
@@ -68,13 +320,62 @@ impl Config {
                 .parse::<u16>()
                 .map_err(handle_errors::Error::ParseError)?,
             db_name,
+            profanity_filter,
+            wordlist,
+            api_layer_url,
+            profanity_cache_size,
+            breaker_failure_threshold,
+            breaker_open_secs,
+            recheck_interval_secs,
+            recheck_batch_size,
+            public_url,
+            mailer,
+            mail_dir,
+            mail_from,
+            smtp_host,
+            smtp_port,
+            require_verified,
+            rate_limit_window_secs,
+            rate_limit_per_ip,
+            rate_limit_per_email,
+            lockout_threshold,
+            lockout_base_secs,
+            lockout_max_secs,
+            trust_forwarded_for,
+            smtp_credentials,
+            bad_words_api_key,
+            keyring,
+            moderation: ModerationPolicy {
+                title: env_value_enum("MODERATION_TITLE", config.moderation.title)?,
+                content: env_value_enum("MODERATION_CONTENT", config.moderation.content)?,
+                flag_threshold,
+                unavailable: env_value_enum(
+                    "PROFANITY_UNAVAILABLE",
+                    config.moderation.unavailable,
+                )?,
+            },
+            argon2,
+            password: PasswordPolicy {
+                min_length: password_min_length,
+                breached_passwords,
+            },
         })
     }
 }
 
+/// Read a `ValueEnum` option from the environment, falling back to the parsed value.
+fn env_value_enum<T: ValueEnum>(key: &str, default: T) -> Result<T, handle_errors::Error> {
+    env::var(key).map_or(Ok(default), |val| {
+        T::from_str(&val, true)
+            .map_err(|_| handle_errors::Error::ConfigError(format!("unknown {key} value {val}")))
+    })
+}
+
 #[cfg(test)]
 mod config_tests {
     use super::*;
+    use crate::keyring::PasetoKey;
+    use crate::types::{ModerationAction, UnavailableAction};
 
     fn set_env() {
         unsafe {
@@ -100,6 +401,43 @@ mod config_tests {
             db_host: "localhost".to_owned(),
             db_port: 5432,
             db_name: "eroteme".to_owned(),
+            profanity_filter: ProfanityBackend::ApiLayer,
+            wordlist: None,
+            api_layer_url: "https://api.apilayer.com".to_owned(),
+            profanity_cache_size: 1024,
+            breaker_failure_threshold: 5,
+            breaker_open_secs: 30,
+            recheck_interval_secs: 60,
+            recheck_batch_size: 20,
+            public_url: "http://localhost:8080".to_owned(),
+            mailer: MailerBackend::Log,
+            mail_dir: None,
+            mail_from: "Eroteme <noreply@localhost>".to_owned(),
+            smtp_host: "localhost".to_owned(),
+            smtp_port: 587,
+            require_verified: false,
+            rate_limit_window_secs: 60,
+            rate_limit_per_ip: 30,
+            rate_limit_per_email: 10,
+            lockout_threshold: 5,
+            lockout_base_secs: 60,
+            lockout_max_secs: 3600,
+            trust_forwarded_for: false,
+            smtp_credentials: None,
+            bad_words_api_key: "API_KEY".to_owned(),
+            keyring: Keyring::new(
+                PasetoKey::new("default", "RANDOM WORDS WINTER MACINTOSH PC").unwrap(),
+                Vec::new(),
+            )
+            .unwrap(),
+            moderation: ModerationPolicy {
+                title: ModerationAction::Censor,
+                content: ModerationAction::Censor,
+                flag_threshold: 3,
+                unavailable: UnavailableAction::Reject,
+            },
+            argon2: Argon2Params::default(),
+            password: PasswordPolicy::default(),
         };
 
         let config = Config::new().unwrap();
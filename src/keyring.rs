@@ -0,0 +1,177 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::{env, fmt};
+
+/// PASETO v2 local tokens are encrypted with a 32 byte key
+pub const KEY_LEN: usize = 32;
+
+/// Id given to `PASETO_KEY` when `PASETO_KEY_ID` isn't set
+const DEFAULT_KEY_ID: &str = "default";
+
+/// Token footer, tells which key of the keyring a token was encrypted with
+#[derive(Deserialize, Serialize, Debug)]
+struct Footer {
+    kid: String,
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct PasetoKey {
+    pub id: String,
+    pub key: [u8; KEY_LEN],
+}
+
+impl PasetoKey {
+    /// # Errors
+    ///
+    /// Will return `Err` if the key isn't exactly 32 bytes long or the id is empty.
+    pub fn new(id: &str, key: &str) -> Result<Self, handle_errors::Error> {
+        if id.is_empty() {
+            return Err(handle_errors::Error::ConfigError(
+                "Paseto key id cannot be empty".to_owned(),
+            ));
+        }
+        let key = key.as_bytes().try_into().map_err(|_| {
+            handle_errors::Error::ConfigError(format!(
+                "Paseto key {id} must be {KEY_LEN} bytes long"
+            ))
+        })?;
+
+        Ok(Self {
+            id: id.to_owned(),
+            key,
+        })
+    }
+
+    /// The footer naming this key, added to every token it encrypts
+    #[must_use]
+    pub fn footer(&self) -> String {
+        serde_json::json!({ "kid": self.id }).to_string()
+    }
+}
+
+impl fmt::Debug for PasetoKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PasetoKey")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Keys used to encrypt and decrypt access tokens. New tokens are encrypted with the current
+/// key, previous keys are kept so tokens issued before a rotation stay valid until they
+/// expire.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Keyring {
+    current: Option<PasetoKey>,
+    previous: Vec<PasetoKey>,
+}
+
+impl Keyring {
+    /// # Errors
+    ///
+    /// Will return `Err` if two keys share an id.
+    pub fn new(current: PasetoKey, previous: Vec<PasetoKey>) -> Result<Self, handle_errors::Error> {
+        let mut ids: Vec<&str> = previous.iter().map(|key| key.id.as_str()).collect();
+        ids.push(&current.id);
+        ids.sort_unstable();
+        if let Some(id) = ids.windows(2).find(|ids| ids[0] == ids[1]) {
+            return Err(handle_errors::Error::ConfigError(format!(
+                "duplicate Paseto key id {}",
+                id[0]
+            )));
+        }
+
+        Ok(Self {
+            current: Some(current),
+            previous,
+        })
+    }
+
+    /// Read the current key from `PASETO_KEY` and `PASETO_KEY_ID`, and previous keys from
+    /// `PASETO_PREVIOUS_KEYS` as a comma separated list of `id:key` pairs.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `PASETO_KEY` is missing or any key is invalid.
+    pub fn from_env() -> Result<Self, handle_errors::Error> {
+        let key = env::var("PASETO_KEY")
+            .map_err(|_| handle_errors::Error::ConfigError("missing Paseto key".to_owned()))?;
+        let id = env::var("PASETO_KEY_ID").unwrap_or_else(|_| DEFAULT_KEY_ID.to_owned());
+        let current = PasetoKey::new(&id, &key)?;
+
+        let previous = env::var("PASETO_PREVIOUS_KEYS")
+            .unwrap_or_default()
+            .split(',')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (id, key) = pair.split_once(':').ok_or_else(|| {
+                    handle_errors::Error::ConfigError(
+                        "previous Paseto keys must be id:key pairs".to_owned(),
+                    )
+                })?;
+                PasetoKey::new(id, key)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Self::new(current, previous)
+    }
+
+    /// The key new tokens are encrypted with, `None` for an empty keyring.
+    #[must_use]
+    pub const fn current(&self) -> Option<&PasetoKey> {
+        self.current.as_ref()
+    }
+
+    /// The key a token was encrypted with, going by its footer.
+    #[must_use]
+    pub fn find(&self, token: &str) -> Option<&PasetoKey> {
+        let footer = token.splitn(4, '.').nth(3)?;
+        let footer = URL_SAFE_NO_PAD.decode(footer).ok()?;
+        let footer: Footer = serde_json::from_slice(&footer).ok()?;
+
+        self.current
+            .iter()
+            .chain(&self.previous)
+            .find(|key| key.id == footer.kid)
+    }
+}
+
+#[cfg(test)]
+mod keyring_tests {
+    use super::{Keyring, PasetoKey};
+
+    const KEY: &str = "RANDOM WORDS WINTER MACINTOSH PC";
+
+    #[test]
+    fn reject_short_keys() {
+        let err = PasetoKey::new("1", "too short").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid configuration: Paseto key 1 must be 32 bytes long"
+        );
+    }
+
+    #[test]
+    fn reject_duplicate_ids() {
+        let key = PasetoKey::new("1", KEY).unwrap();
+        assert!(Keyring::new(key.clone(), vec![key]).is_err());
+    }
+
+    #[test]
+    fn find_key_by_footer() {
+        let current = PasetoKey::new("2", KEY).unwrap();
+        let previous = PasetoKey::new("1", "ANOTHER KEY OF THIRTY TWO BYTES!").unwrap();
+        let keyring = Keyring::new(current, vec![previous.clone()]).unwrap();
+
+        let token = paseto::tokens::PasetoBuilder::new()
+            .set_encryption_key(&previous.key)
+            .set_footer(&previous.footer())
+            .set_claim("account_id", serde_json::json!(1))
+            .build()
+            .unwrap();
+
+        assert_eq!(keyring.find(&token), Some(&previous));
+        assert_eq!(keyring.find("v2.local.payload"), None);
+    }
+}
@@ -1,12 +1,23 @@
+#![recursion_limit = "256"]
+
 use std::net;
+use std::time::Duration;
 
 use tokio::sync::oneshot::{self, Sender};
 
 pub mod config;
 pub use config::Config;
 
+pub mod keyring;
+pub use keyring::Keyring;
+
+pub mod limits;
+
+pub mod mailer;
+
 pub mod profanity;
-use profanity::check_profanity;
+
+pub mod recheck;
 
 pub mod router;
 use router::build_routes;
@@ -23,6 +34,14 @@ pub struct OneShotHandler {
 }
 
 pub async fn run(config: Config, store: Store) {
+    if config.recheck_interval_secs > 0 {
+        tokio::task::spawn(recheck::run(
+            store.clone(),
+            Duration::from_secs(config.recheck_interval_secs),
+            config.recheck_batch_size,
+        ));
+    }
+
     let routes = build_routes(store).await;
     warp::serve(routes).run(([0, 0, 0, 0], config.port)).await;
 }
@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Tracked keys are pruned once there are this many, so one client cycling through
+/// addresses or emails can't grow the maps without bound
+const PRUNE_AT: usize = 10_000;
+
+/// How many requests a key may make per window, 0 disables the limit
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RateLimitPolicy {
+    pub max_requests: u32,
+    pub window: Duration,
+}
+
+/// Counts requests per key in fixed windows and refuses them once a window is used up.
+#[derive(Debug)]
+pub struct RateLimiter {
+    policy: RateLimitPolicy,
+    windows: Mutex<HashMap<String, (Instant, u32)>>,
+}
+
+impl RateLimiter {
+    #[must_use]
+    pub fn new(policy: RateLimitPolicy) -> Self {
+        Self {
+            policy,
+            windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Count a request for `key`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` with the seconds until the window resets if the key is over the
+    /// limit.
+    pub fn check(&self, key: &str) -> Result<(), handle_errors::Error> {
+        if self.policy.max_requests == 0 {
+            return Ok(());
+        }
+
+        let now = Instant::now();
+        let mut windows = self.windows.lock().unwrap_or_else(PoisonError::into_inner);
+        if windows.len() >= PRUNE_AT {
+            windows.retain(|_, (start, _)| now < *start + self.policy.window);
+        }
+
+        let (start, count) = windows.entry(key.to_owned()).or_insert((now, 0));
+        if now >= *start + self.policy.window {
+            *start = now;
+            *count = 0;
+        }
+        if *count >= self.policy.max_requests {
+            return Err(too_many_requests(*start + self.policy.window - now));
+        }
+        *count += 1;
+        Ok(())
+    }
+}
+
+/// When repeated wrong passwords lock an email out, 0 failures disables the lockout
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LockoutPolicy {
+    /// Consecutive failures after which logins are refused
+    pub threshold: u32,
+    /// Length of the first lockout, doubled with every failure after it
+    pub base: Duration,
+    /// Longest a lockout lasts
+    pub max: Duration,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Failures {
+    count: u32,
+    last: Instant,
+    locked_until: Option<Instant>,
+}
+
+/// Locks emails out of logging in after consecutive wrong passwords, for exponentially
+/// longer each time. Emails without an account are tracked the same way, so a lockout
+/// doesn't tell whether an account exists.
+#[derive(Debug)]
+pub struct Lockout {
+    policy: LockoutPolicy,
+    failures: Mutex<HashMap<String, Failures>>,
+}
+
+impl Lockout {
+    #[must_use]
+    pub fn new(policy: LockoutPolicy) -> Self {
+        Self {
+            policy,
+            failures: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// # Errors
+    ///
+    /// Will return `Err` with the seconds until the lockout ends if the email is locked out.
+    pub fn check(&self, email: &str) -> Result<(), handle_errors::Error> {
+        let failures = self.failures.lock().unwrap_or_else(PoisonError::into_inner);
+        let now = Instant::now();
+        match failures.get(email).and_then(|f| f.locked_until) {
+            Some(until) if now < until => Err(too_many_requests(until - now)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Record a wrong password, locking the email out once it reaches the threshold.
+    pub fn record_failure(&self, email: &str) {
+        if self.policy.threshold == 0 {
+            return;
+        }
+
+        let now = Instant::now();
+        let mut failures = self.failures.lock().unwrap_or_else(PoisonError::into_inner);
+        if failures.len() >= PRUNE_AT {
+            // Failures older than the longest lockout no longer matter
+            failures.retain(|_, f| now < f.last + self.policy.max);
+        }
+
+        let entry = failures.entry(email.to_owned()).or_insert(Failures {
+            count: 0,
+            last: now,
+            locked_until: None,
+        });
+        if now >= entry.last + self.policy.max {
+            entry.count = 0;
+        }
+        entry.count += 1;
+        entry.last = now;
+
+        if entry.count >= self.policy.threshold {
+            let lockout = self.lockout_for(entry.count - self.policy.threshold);
+            tracing::event!(
+                tracing::Level::WARN,
+                "login locked for {}s after {} failures",
+                lockout.as_secs(),
+                entry.count
+            );
+            entry.locked_until = Some(now + lockout);
+        }
+    }
+
+    /// Forget the failures of an email after a successful login.
+    pub fn clear(&self, email: &str) {
+        self.failures
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(email);
+    }
+
+    fn lockout_for(&self, doublings: u32) -> Duration {
+        self.policy
+            .base
+            .checked_mul(2_u32.saturating_pow(doublings))
+            .map_or(self.policy.max, |lockout| lockout.min(self.policy.max))
+    }
+}
+
+/// Limits on the unauthenticated account routes, login, registration and password resets
+#[derive(Debug)]
+pub struct AuthLimits {
+    pub by_ip: RateLimiter,
+    pub by_email: RateLimiter,
+    pub lockout: Lockout,
+    /// Read the client address from `X-Forwarded-For`, only safe behind a proxy that sets it
+    pub trust_forwarded_for: bool,
+}
+
+impl AuthLimits {
+    /// Count a request to `route` against the limits of the client address and the email.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if either is over its limit.
+    pub fn check(
+        &self,
+        route: &str,
+        ip: Option<IpAddr>,
+        email: &str,
+    ) -> Result<(), handle_errors::Error> {
+        self.check_ip(route, ip)?;
+        self.by_email.check(&format!("{route}:{email}"))
+    }
+
+    /// Count a request to `route` against the limit of the client address only, for routes
+    /// that don't name an email.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the address is over its limit.
+    pub fn check_ip(&self, route: &str, ip: Option<IpAddr>) -> Result<(), handle_errors::Error> {
+        match ip {
+            Some(ip) => self.by_ip.check(&format!("{route}:{ip}")),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Emails are compared case insensitively, so changing the case doesn't reset a limit
+#[must_use]
+pub fn limit_key(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+/// `Retry-After` is given in whole seconds, rounded up and at least one
+fn too_many_requests(retry_after: Duration) -> handle_errors::Error {
+    let secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+    handle_errors::Error::TooManyRequests(secs.max(1))
+}
+
+#[cfg(test)]
+mod limits_tests {
+    use super::{Lockout, LockoutPolicy, RateLimitPolicy, RateLimiter};
+    use std::time::Duration;
+
+    #[test]
+    fn refuse_requests_over_the_limit() {
+        let limiter = RateLimiter::new(RateLimitPolicy {
+            max_requests: 2,
+            window: Duration::from_secs(60),
+        });
+
+        assert!(limiter.check("a").is_ok());
+        assert!(limiter.check("a").is_ok());
+        let err = limiter.check("a").unwrap_err();
+        assert!(matches!(err, handle_errors::Error::TooManyRequests(60)));
+        // Other keys have their own window
+        assert!(limiter.check("b").is_ok());
+    }
+
+    #[test]
+    fn reset_after_the_window() {
+        let limiter = RateLimiter::new(RateLimitPolicy {
+            max_requests: 1,
+            window: Duration::ZERO,
+        });
+
+        assert!(limiter.check("a").is_ok());
+        assert!(limiter.check("a").is_ok());
+    }
+
+    #[test]
+    fn lock_out_exponentially() {
+        let lockout = Lockout::new(LockoutPolicy {
+            threshold: 2,
+            base: Duration::from_secs(10),
+            max: Duration::from_secs(25),
+        });
+
+        lockout.record_failure("a");
+        assert!(lockout.check("a").is_ok());
+
+        lockout.record_failure("a");
+        let err = lockout.check("a").unwrap_err();
+        assert!(matches!(err, handle_errors::Error::TooManyRequests(10)));
+
+        lockout.record_failure("a");
+        let err = lockout.check("a").unwrap_err();
+        assert!(matches!(err, handle_errors::Error::TooManyRequests(20)));
+
+        // Capped at the maximum
+        lockout.record_failure("a");
+        let err = lockout.check("a").unwrap_err();
+        assert!(matches!(err, handle_errors::Error::TooManyRequests(25)));
+
+        lockout.clear("a");
+        assert!(lockout.check("a").is_ok());
+    }
+}
@@ -0,0 +1,72 @@
+use crate::mailer::{Email, Mailer};
+use async_trait::async_trait;
+use chrono::Utc;
+use std::path::PathBuf;
+use tokio::fs;
+
+/// Mailer for local testing, nothing is sent. Emails are written to one file each in a
+/// directory, or to the log if no directory is given.
+#[derive(Clone, Debug, Default)]
+pub struct LogMailer {
+    dir: Option<PathBuf>,
+}
+
+impl LogMailer {
+    #[must_use]
+    pub const fn new(dir: Option<PathBuf>) -> Self {
+        Self { dir }
+    }
+}
+
+#[async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, email: Email) -> Result<(), handle_errors::Error> {
+        let message = format!(
+            "To: {}\nSubject: {}\n\n{}\n",
+            email.to, email.subject, email.body
+        );
+
+        let Some(dir) = &self.dir else {
+            tracing::event!(target: "eroteme", tracing::Level::INFO, "{message}");
+            return Ok(());
+        };
+
+        let path = dir.join(format!(
+            "{}-{}.eml",
+            Utc::now().format("%Y%m%d%H%M%S%f"),
+            email.to.replace(['/', '\\'], "_")
+        ));
+        fs::write(&path, message)
+            .await
+            .map_err(|e| handle_errors::Error::MailError(format!("{}: {e}", path.display())))
+    }
+}
+
+#[cfg(test)]
+mod log_tests {
+    use super::{Email, LogMailer, Mailer};
+    use std::env;
+
+    #[tokio::test]
+    async fn write_email_to_dir() {
+        let dir = env::temp_dir().join(format!("eroteme-mail-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        LogMailer::new(Some(dir.clone()))
+            .send(Email {
+                to: "test@email.com".to_owned(),
+                subject: "Hello".to_owned(),
+                body: "Verify your email".to_owned(),
+            })
+            .await
+            .unwrap();
+
+        let entry = std::fs::read_dir(&dir).unwrap().next().unwrap().unwrap();
+        let message = std::fs::read_to_string(entry.path()).unwrap();
+        assert_eq!(
+            message,
+            "To: test@email.com\nSubject: Hello\n\nVerify your email\n"
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
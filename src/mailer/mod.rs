@@ -0,0 +1,25 @@
+use async_trait::async_trait;
+use std::fmt::Debug;
+
+pub mod log;
+pub use log::LogMailer;
+
+pub mod smtp;
+pub use smtp::SmtpMailer;
+
+/// A plain text email to a single recipient
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Backend used to send emails to account holders
+#[async_trait]
+pub trait Mailer: Debug + Send + Sync {
+    /// # Errors
+    ///
+    /// Will return `Err` if the email cannot be sent.
+    async fn send(&self, email: Email) -> Result<(), handle_errors::Error>;
+}
@@ -0,0 +1,75 @@
+use crate::mailer::{Email, Mailer};
+use async_trait::async_trait;
+use lettre::message::{header::ContentType, Mailbox};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use std::fmt;
+
+/// Sends email through an SMTP relay over STARTTLS
+#[derive(Clone)]
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    /// The connection pool to the relay is built once and shared by every email.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the relay or the sender address is invalid.
+    pub fn new(
+        host: &str,
+        port: u16,
+        credentials: Option<(String, String)>,
+        from: &str,
+    ) -> Result<Self, handle_errors::Error> {
+        let mut transport = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+            .map_err(|e| handle_errors::Error::ConfigError(format!("invalid SMTP relay: {e}")))?
+            .port(port);
+        if let Some((username, password)) = credentials {
+            transport = transport.credentials(Credentials::new(username, password));
+        }
+
+        let from = from.parse().map_err(|e| {
+            handle_errors::Error::ConfigError(format!("invalid sender address {from}: {e}"))
+        })?;
+
+        Ok(Self {
+            transport: transport.build(),
+            from,
+        })
+    }
+}
+
+impl fmt::Debug for SmtpMailer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SmtpMailer")
+            .field("from", &self.from)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, email: Email) -> Result<(), handle_errors::Error> {
+        let to = email
+            .to
+            .parse::<Mailbox>()
+            .map_err(|e| handle_errors::Error::MailError(e.to_string()))?;
+
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(email.subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(email.body)
+            .map_err(|e| handle_errors::Error::MailError(e.to_string()))?;
+
+        self.transport
+            .send(message)
+            .await
+            .map(|_| ())
+            .map_err(|e| handle_errors::Error::MailError(e.to_string()))
+    }
+}
@@ -0,0 +1,258 @@
+use crate::profanity::{BadWord, Censored, ProfanityFilter};
+use async_trait::async_trait;
+use futures_util::future;
+use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
+use reqwest_retry::{policies::ExponentialBackoff, RetryTransientMiddleware};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct APIResponse {
+    message: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct BadWordsResponse {
+    content: String,
+    bad_words_total: i64,
+    bad_words_list: Vec<BadWord>,
+    censored_content: String,
+}
+
+/// Joins batched content into one request. Neither the separator nor its surrounding
+/// newlines can be censored, so the censored content splits back into the same parts.
+const BATCH_SEPARATOR: &str = "\n\n\u{241e}\n\n";
+
+/// Profanity filter backed by the APILayer bad words API
+#[derive(Clone)]
+pub struct ApiLayerFilter {
+    api_key: String,
+    api_layer_url: String,
+    client: ClientWithMiddleware,
+}
+
+impl ApiLayerFilter {
+    /// The HTTP client and its retry policy are built once and shared by every check.
+    #[must_use]
+    pub fn new(api_key: String, api_layer_url: String) -> Self {
+        let retry_policy = ExponentialBackoff::builder().build_with_max_retries(3);
+        let client = ClientBuilder::new(reqwest::Client::new())
+            .with(RetryTransientMiddleware::new_with_policy(retry_policy))
+            .build();
+
+        Self {
+            api_key,
+            api_layer_url,
+            client,
+        }
+    }
+
+    async fn bad_words(&self, content: String) -> Result<BadWordsResponse, handle_errors::Error> {
+        let res = self
+            .client
+            .post(format!(
+                "{}/bad_words?censor_character=*",
+                self.api_layer_url
+            ))
+            .header("apikey", &self.api_key)
+            .body(content)
+            .send()
+            .await
+            .map_err(handle_errors::Error::MiddlewareReqwestError)?;
+
+        if !res.status().is_success() {
+            if res.status().is_client_error() {
+                let err = transform_error(res).await;
+                return Err(handle_errors::Error::ClientError(err));
+            }
+            let err = transform_error(res).await;
+            return Err(handle_errors::Error::ServerError(err));
+        }
+
+        res.json::<BadWordsResponse>()
+            .await
+            .map_err(handle_errors::Error::ReqwestAPIError)
+    }
+
+    async fn check_each(
+        &self,
+        contents: Vec<String>,
+    ) -> Result<Vec<Censored>, handle_errors::Error> {
+        future::try_join_all(
+            contents
+                .into_iter()
+                .map(|content| self.check_profanity(content)),
+        )
+        .await
+    }
+}
+
+impl fmt::Debug for ApiLayerFilter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ApiLayerFilter")
+            .field("api_layer_url", &self.api_layer_url)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl ProfanityFilter for ApiLayerFilter {
+    /// # Errors
+    ///
+    /// Will return `Err` if the API call responds with an error.
+    async fn check_profanity(&self, content: String) -> Result<Censored, handle_errors::Error> {
+        let res = self.bad_words(content.clone()).await?;
+
+        // `censored_content` is empty when nothing was found
+        if res.bad_words_total == 0 {
+            return Ok(Censored::clean(content));
+        }
+        Ok(Censored {
+            content: res.censored_content,
+            bad_words: res.bad_words_list,
+        })
+    }
+
+    /// Sends all content in one request, falling back to one request each if the content
+    /// contains the batch separator or the response can't be split back up.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the API call responds with an error.
+    async fn check_profanity_batch(
+        &self,
+        contents: Vec<String>,
+    ) -> Result<Vec<Censored>, handle_errors::Error> {
+        if contents.len() < 2 || contents.iter().any(|c| c.contains(BATCH_SEPARATOR)) {
+            return self.check_each(contents).await;
+        }
+
+        let res = self.bad_words(contents.join(BATCH_SEPARATOR)).await?;
+        if res.bad_words_total == 0 {
+            return Ok(contents.into_iter().map(Censored::clean).collect());
+        }
+
+        match split_batch(&contents, res) {
+            Some(results) => Ok(results),
+            None => {
+                tracing::event!(
+                    tracing::Level::WARN,
+                    "cannot split batched profanity check, checking one by one"
+                );
+                self.check_each(contents).await
+            }
+        }
+    }
+}
+
+/// Split a batched response back into one result per part, moving each bad word to the part
+/// it was found in with offsets relative to that part.
+fn split_batch(contents: &[String], res: BadWordsResponse) -> Option<Vec<Censored>> {
+    let parts: Vec<&str> = res.censored_content.split(BATCH_SEPARATOR).collect();
+    if parts.len() != contents.len() {
+        return None;
+    }
+
+    let separator_len = i64::try_from(BATCH_SEPARATOR.chars().count()).ok()?;
+    let mut starts = Vec::with_capacity(contents.len());
+    let mut start = 0;
+    for content in contents {
+        starts.push(start);
+        start += i64::try_from(content.chars().count()).ok()? + separator_len;
+    }
+
+    let mut bad_words = vec![Vec::new(); contents.len()];
+    for mut bad_word in res.bad_words_list {
+        let part = starts.iter().rposition(|start| *start <= bad_word.start)?;
+        bad_word.start -= starts[part];
+        bad_word.end -= starts[part];
+        bad_words[part].push(bad_word);
+    }
+
+    Some(
+        contents
+            .iter()
+            .zip(parts)
+            .zip(bad_words)
+            .map(|((content, part), bad_words)| {
+                if bad_words.is_empty() {
+                    Censored::clean(content.clone())
+                } else {
+                    Censored {
+                        content: part.to_owned(),
+                        bad_words,
+                    }
+                }
+            })
+            .collect(),
+    )
+}
+
+async fn transform_error(res: reqwest::Response) -> handle_errors::APILayerError {
+    handle_errors::APILayerError {
+        status: res.status().as_u16(),
+        message: res
+            .json::<APIResponse>()
+            .await
+            .expect("message missing in api response")
+            .message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ApiLayerFilter, ProfanityFilter};
+    use mock_server::{MockServer, OneshotHandler};
+
+    #[tokio::test]
+    async fn run() {
+        let handler = run_mock();
+        let filter = ApiLayerFilter::new("YES".to_owned(), "http://127.0.0.1:3030".to_owned());
+        censor_profane_words(&filter).await;
+        no_profane_words(&filter).await;
+        batch(&filter).await;
+        let _ = handler.sender.send(1);
+    }
+
+    fn run_mock() -> OneshotHandler {
+        let socket = "127.0.0.1:3030"
+            .to_owned()
+            .parse()
+            .expect("not a valid address");
+        let mock = MockServer::new(socket);
+        mock.oneshot()
+    }
+
+    async fn censor_profane_words(filter: &ApiLayerFilter) {
+        let content = "This is a shitty sentence".to_owned();
+        let censored = filter.check_profanity(content).await.unwrap();
+        assert_eq!(censored.content, "this is a ****** sentence");
+        assert_eq!(censored.bad_words.len(), 1);
+        assert_eq!(censored.bad_words[0].word, "shitty");
+    }
+
+    async fn no_profane_words(filter: &ApiLayerFilter) {
+        let content = "This is a sentence".to_owned();
+        let censored = filter.check_profanity(content.clone()).await.unwrap();
+        assert_eq!(censored.content, content);
+        assert!(censored.is_clean());
+    }
+
+    async fn batch(filter: &ApiLayerFilter) {
+        let contents = vec![
+            "A title".to_owned(),
+            "This is a shitty sentence".to_owned(),
+            "Shitty, shitty".to_owned(),
+        ];
+        let results = filter.check_profanity_batch(contents).await.unwrap();
+        assert_eq!(results[0].content, "A title");
+        assert!(results[0].is_clean());
+        assert_eq!(results[1].content, "this is a ****** sentence");
+        assert_eq!(
+            (results[1].bad_words[0].start, results[1].bad_words[0].end),
+            (10, 16)
+        );
+        assert_eq!(results[2].content, "******, ******");
+        assert_eq!(results[2].bad_words.len(), 2);
+    }
+}
@@ -0,0 +1,335 @@
+use crate::profanity::{Censored, ProfanityFilter, ProfanityMetrics};
+use async_trait::async_trait;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// When to stop calling a failing profanity filter and for how long
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BreakerPolicy {
+    /// Consecutive failures after which the circuit opens
+    pub failure_threshold: u32,
+    /// How long the circuit stays open before a trial check is let through
+    pub open_for: Duration,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    Closed {
+        failures: u32,
+    },
+    Open {
+        until: Instant,
+    },
+    /// A trial check is in flight, every other check is refused until it completes
+    HalfOpen,
+}
+
+/// Wraps another profanity filter with a circuit breaker. After `failure_threshold`
+/// consecutive outages the wrapped filter isn't called until `open_for` has passed, checks
+/// fail straight away with `ProfanityUnavailable` instead.
+///
+/// Errors caused by the content itself, such as a `4xx` from the API, don't count as
+/// failures and are passed through unchanged.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    inner: Arc<dyn ProfanityFilter>,
+    policy: BreakerPolicy,
+    state: Mutex<State>,
+}
+
+impl CircuitBreaker {
+    #[must_use]
+    pub fn new(inner: Arc<dyn ProfanityFilter>, policy: BreakerPolicy) -> Self {
+        Self {
+            inner,
+            policy,
+            state: Mutex::new(State::Closed { failures: 0 }),
+        }
+    }
+
+    /// Whether the wrapped filter may be called, or the number of seconds until it may be.
+    fn acquire(&self) -> Result<Call<'_>, handle_errors::Error> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        match *state {
+            State::Closed { .. } => Ok(Call {
+                breaker: self,
+                trial: false,
+            }),
+            State::Open { until } => {
+                let now = Instant::now();
+                if now < until {
+                    return Err(unavailable(until - now));
+                }
+                *state = State::HalfOpen;
+                Ok(Call {
+                    breaker: self,
+                    trial: true,
+                })
+            }
+            State::HalfOpen => Err(unavailable(self.policy.open_for)),
+        }
+    }
+
+    /// Record the outcome of a call to the wrapped filter, turning outages into
+    /// `ProfanityUnavailable`.
+    fn record<T>(
+        &self,
+        mut call: Call<'_>,
+        res: Result<T, handle_errors::Error>,
+    ) -> Result<T, handle_errors::Error> {
+        call.trial = false;
+        drop(call);
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        match res {
+            Err(e) if is_outage(&e) => {
+                tracing::event!(tracing::Level::WARN, "profanity filter failed: {e}");
+                let failures = match *state {
+                    State::Closed { failures } => failures + 1,
+                    State::Open { .. } | State::HalfOpen => self.policy.failure_threshold,
+                };
+                if failures >= self.policy.failure_threshold {
+                    tracing::event!(tracing::Level::ERROR, "profanity filter circuit opened");
+                    *state = State::Open {
+                        until: Instant::now() + self.policy.open_for,
+                    };
+                    Err(unavailable(self.policy.open_for))
+                } else {
+                    *state = State::Closed { failures };
+                    Err(unavailable(Duration::ZERO))
+                }
+            }
+            res => {
+                if *state == State::HalfOpen {
+                    tracing::event!(tracing::Level::INFO, "profanity filter circuit closed");
+                }
+                *state = State::Closed { failures: 0 };
+                res
+            }
+        }
+    }
+}
+
+/// A call let through to the wrapped filter. A trial call that is dropped before its
+/// outcome is recorded, such as when the client disconnects, reopens the circuit so the
+/// next check becomes the trial instead of the breaker staying half open.
+struct Call<'a> {
+    breaker: &'a CircuitBreaker,
+    trial: bool,
+}
+
+impl Drop for Call<'_> {
+    fn drop(&mut self) {
+        if !self.trial {
+            return;
+        }
+        let mut state = self
+            .breaker
+            .state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if *state == State::HalfOpen {
+            *state = State::Open {
+                until: Instant::now(),
+            };
+        }
+    }
+}
+
+#[async_trait]
+impl ProfanityFilter for CircuitBreaker {
+    async fn check_profanity(&self, content: String) -> Result<Censored, handle_errors::Error> {
+        let call = self.acquire()?;
+        let res = self.inner.check_profanity(content).await;
+        self.record(call, res)
+    }
+
+    async fn check_profanity_batch(
+        &self,
+        contents: Vec<String>,
+    ) -> Result<Vec<Censored>, handle_errors::Error> {
+        let call = self.acquire()?;
+        let res = self.inner.check_profanity_batch(contents).await;
+        self.record(call, res)
+    }
+
+    fn metrics(&self) -> Option<ProfanityMetrics> {
+        self.inner.metrics()
+    }
+}
+
+/// Transport errors and `5xx` responses, as opposed to the API refusing the content
+const fn is_outage(e: &handle_errors::Error) -> bool {
+    matches!(
+        e,
+        handle_errors::Error::ServerError(_)
+            | handle_errors::Error::MiddlewareReqwestError(_)
+            | handle_errors::Error::ReqwestAPIError(_)
+            | handle_errors::Error::ProfanityUnavailable(_)
+    )
+}
+
+/// `Retry-After` is given in whole seconds, rounded up and at least one
+fn unavailable(retry_after: Duration) -> handle_errors::Error {
+    let secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+    handle_errors::Error::ProfanityUnavailable(secs.max(1))
+}
+
+#[cfg(test)]
+mod breaker_tests {
+    use super::{BreakerPolicy, CircuitBreaker, ProfanityFilter};
+    use crate::profanity::{Censored, WordlistFilter};
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    /// Fails with a server error while `down` is set and never answers while `hung` is set,
+    /// counting every call
+    #[derive(Debug, Default)]
+    struct Flaky {
+        down: AtomicBool,
+        hung: AtomicBool,
+        calls: AtomicU32,
+    }
+
+    #[async_trait]
+    impl ProfanityFilter for Flaky {
+        async fn check_profanity(&self, content: String) -> Result<Censored, handle_errors::Error> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self.hung.load(Ordering::SeqCst) {
+                std::future::pending::<()>().await;
+            }
+            if self.down.load(Ordering::SeqCst) {
+                return Err(handle_errors::Error::ServerError(
+                    handle_errors::APILayerError {
+                        status: 503,
+                        message: "down".to_owned(),
+                    },
+                ));
+            }
+            Ok(WordlistFilter::new(["shitty"]).censor(&content))
+        }
+    }
+
+    fn breaker(flaky: &Arc<Flaky>, open_for: Duration) -> CircuitBreaker {
+        CircuitBreaker::new(
+            flaky.clone(),
+            BreakerPolicy {
+                failure_threshold: 2,
+                open_for,
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn open_after_consecutive_failures() {
+        let flaky = Arc::new(Flaky::default());
+        flaky.down.store(true, Ordering::SeqCst);
+        let breaker = breaker(&flaky, Duration::from_secs(30));
+
+        for _ in 0..4 {
+            let err = breaker
+                .check_profanity("content".to_owned())
+                .await
+                .unwrap_err();
+            assert!(matches!(err, handle_errors::Error::ProfanityUnavailable(_)));
+        }
+
+        // The last two checks never reached the filter
+        assert_eq!(flaky.calls.load(Ordering::SeqCst), 2);
+        let err = breaker
+            .check_profanity("content".to_owned())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            handle_errors::Error::ProfanityUnavailable(30)
+        ));
+    }
+
+    #[tokio::test]
+    async fn close_after_successful_trial() {
+        let flaky = Arc::new(Flaky::default());
+        flaky.down.store(true, Ordering::SeqCst);
+        let breaker = breaker(&flaky, Duration::ZERO);
+
+        for _ in 0..2 {
+            breaker
+                .check_profanity("content".to_owned())
+                .await
+                .unwrap_err();
+        }
+
+        flaky.down.store(false, Ordering::SeqCst);
+        let censored = breaker
+            .check_profanity("A shitty day".to_owned())
+            .await
+            .unwrap();
+        assert_eq!(censored.content, "A ****** day");
+
+        // Closed again, a single failure doesn't open it
+        flaky.down.store(true, Ordering::SeqCst);
+        breaker
+            .check_profanity("content".to_owned())
+            .await
+            .unwrap_err();
+        assert_eq!(flaky.calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn reopen_after_failed_trial() {
+        let flaky = Arc::new(Flaky::default());
+        flaky.down.store(true, Ordering::SeqCst);
+        let breaker = breaker(&flaky, Duration::ZERO);
+
+        for _ in 0..3 {
+            breaker
+                .check_profanity("content".to_owned())
+                .await
+                .unwrap_err();
+        }
+
+        // Two failures opened the circuit, the trial failed and opened it straight away
+        assert_eq!(flaky.calls.load(Ordering::SeqCst), 3);
+        assert!(matches!(
+            *breaker.state.lock().unwrap(),
+            super::State::Open { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn reopen_after_cancelled_trial() {
+        let flaky = Arc::new(Flaky::default());
+        flaky.down.store(true, Ordering::SeqCst);
+        let breaker = breaker(&flaky, Duration::ZERO);
+
+        for _ in 0..2 {
+            breaker
+                .check_profanity("content".to_owned())
+                .await
+                .unwrap_err();
+        }
+
+        // The trial never answers and is dropped, as when the client disconnects
+        flaky.hung.store(true, Ordering::SeqCst);
+        tokio::time::timeout(
+            Duration::from_millis(10),
+            breaker.check_profanity("content".to_owned()),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(
+            *breaker.state.lock().unwrap(),
+            super::State::Open { .. }
+        ));
+
+        // The next check is let through as a new trial
+        flaky.hung.store(false, Ordering::SeqCst);
+        flaky.down.store(false, Ordering::SeqCst);
+        let censored = breaker
+            .check_profanity("A shitty day".to_owned())
+            .await
+            .unwrap();
+        assert_eq!(censored.content, "A ****** day");
+    }
+}
@@ -0,0 +1,183 @@
+use crate::profanity::{Censored, ProfanityFilter, ProfanityMetrics};
+use async_trait::async_trait;
+use hashlink::LruCache;
+use sha2::{Digest, Sha256};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+
+type ContentHash = [u8; 32];
+
+/// Wraps another profanity filter with an in-memory LRU cache of recent results, keyed by the
+/// SHA-256 hash of the checked content.
+#[derive(Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub struct CachedFilter {
+    inner: Arc<dyn ProfanityFilter>,
+    cache: Mutex<LruCache<ContentHash, Censored>>,
+    capacity: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl CachedFilter {
+    #[must_use]
+    pub fn new(inner: Arc<dyn ProfanityFilter>, capacity: usize) -> Self {
+        Self {
+            inner,
+            cache: Mutex::new(LruCache::new(capacity)),
+            capacity,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    fn get(&self, key: &ContentHash) -> Option<Censored> {
+        // A panic while holding the lock can't leave the cache half updated, so a poisoned
+        // lock is still safe to use
+        let cached = self
+            .cache
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(key)
+            .cloned();
+
+        if cached.is_some() {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+        }
+        cached
+    }
+
+    fn insert(&self, key: ContentHash, censored: Censored) {
+        self.cache
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(key, censored);
+    }
+}
+
+#[async_trait]
+impl ProfanityFilter for CachedFilter {
+    async fn check_profanity(&self, content: String) -> Result<Censored, handle_errors::Error> {
+        let key = hash(&content);
+        if let Some(censored) = self.get(&key) {
+            return Ok(censored);
+        }
+
+        let censored = self.inner.check_profanity(content).await?;
+        self.insert(key, censored.clone());
+        Ok(censored)
+    }
+
+    /// Only content missing from the cache is passed on to the wrapped filter, as one batch.
+    async fn check_profanity_batch(
+        &self,
+        contents: Vec<String>,
+    ) -> Result<Vec<Censored>, handle_errors::Error> {
+        let keys: Vec<ContentHash> = contents.iter().map(|content| hash(content)).collect();
+        let mut results: Vec<Option<Censored>> = keys.iter().map(|key| self.get(key)).collect();
+
+        let (missing, uncached): (Vec<usize>, Vec<String>) = contents
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| results[*i].is_none())
+            .unzip();
+
+        if !uncached.is_empty() {
+            let checked = self.inner.check_profanity_batch(uncached).await?;
+            for (i, censored) in missing.into_iter().zip(checked) {
+                self.insert(keys[i], censored.clone());
+                results[i] = Some(censored);
+            }
+        }
+
+        Ok(results.into_iter().flatten().collect())
+    }
+
+    fn metrics(&self) -> Option<ProfanityMetrics> {
+        let cache_hits = self.hits.load(Ordering::Relaxed);
+        let cache_misses = self.misses.load(Ordering::Relaxed);
+        let checks = cache_hits + cache_misses;
+
+        #[allow(clippy::cast_precision_loss)]
+        let cache_hit_rate = if checks == 0 {
+            0.0
+        } else {
+            cache_hits as f64 / checks as f64
+        };
+
+        Some(ProfanityMetrics {
+            cache_hits,
+            cache_misses,
+            cache_hit_rate,
+            cached_entries: self
+                .cache
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .len(),
+            cache_capacity: self.capacity,
+        })
+    }
+}
+
+fn hash(content: &str) -> ContentHash {
+    Sha256::digest(content.as_bytes()).into()
+}
+
+#[cfg(test)]
+mod cache_tests {
+    use super::{CachedFilter, ProfanityFilter};
+    use crate::profanity::WordlistFilter;
+    use std::sync::Arc;
+
+    fn filter(capacity: usize) -> CachedFilter {
+        CachedFilter::new(Arc::new(WordlistFilter::new(["shitty"])), capacity)
+    }
+
+    #[tokio::test]
+    async fn cache_results() {
+        let filter = filter(2);
+        for _ in 0..3 {
+            let censored = filter
+                .check_profanity("A shitty day".to_owned())
+                .await
+                .unwrap();
+            assert_eq!(censored.content, "A ****** day");
+        }
+
+        let metrics = filter.metrics().unwrap();
+        assert_eq!((metrics.cache_hits, metrics.cache_misses), (2, 1));
+        assert!((metrics.cache_hit_rate - 2.0 / 3.0).abs() < f64::EPSILON);
+        assert_eq!(metrics.cached_entries, 1);
+    }
+
+    #[tokio::test]
+    async fn evict_least_recently_used() {
+        let filter = filter(2);
+        for content in ["one", "two", "one", "three", "two"] {
+            filter.check_profanity(content.to_owned()).await.unwrap();
+        }
+
+        // `two` was evicted by `three` and had to be checked again
+        let metrics = filter.metrics().unwrap();
+        assert_eq!((metrics.cache_hits, metrics.cache_misses), (1, 4));
+        assert_eq!(metrics.cached_entries, 2);
+    }
+
+    #[tokio::test]
+    async fn batch_only_checks_missing_content() {
+        let filter = filter(8);
+        filter.check_profanity("A title".to_owned()).await.unwrap();
+
+        let results = filter
+            .check_profanity_batch(vec!["A title".to_owned(), "shitty content".to_owned()])
+            .await
+            .unwrap();
+        assert_eq!(results[0].content, "A title");
+        assert_eq!(results[1].content, "****** content");
+
+        let metrics = filter.metrics().unwrap();
+        assert_eq!((metrics.cache_hits, metrics.cache_misses), (1, 2));
+    }
+}
@@ -0,0 +1,106 @@
+use async_trait::async_trait;
+use futures_util::future;
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+
+pub mod api_layer;
+pub use api_layer::ApiLayerFilter;
+
+pub mod breaker;
+pub use breaker::{BreakerPolicy, CircuitBreaker};
+
+pub mod cache;
+pub use cache::CachedFilter;
+
+pub mod wordlist;
+pub use wordlist::WordlistFilter;
+
+/// A profane word found in the checked content
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct BadWord {
+    /// The text as it appeared in the content
+    pub original: String,
+    /// The listed word it matched
+    pub word: String,
+    /// Number of characters that differ from the listed word
+    pub deviations: i64,
+    pub info: i64,
+    #[serde(rename = "replacedLen")]
+    pub replaced_len: i64,
+    /// Character offset of the word in the checked content
+    #[serde(default)]
+    pub start: i64,
+    /// Character offset just past the end of the word
+    #[serde(default)]
+    pub end: i64,
+}
+
+/// Result of a profanity check, the censored content and every word that was censored
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Censored {
+    pub content: String,
+    pub bad_words: Vec<BadWord>,
+}
+
+impl Censored {
+    /// Content without any profanity, returned unchanged.
+    #[must_use]
+    pub const fn clean(content: String) -> Self {
+        Self {
+            content,
+            bad_words: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn is_clean(&self) -> bool {
+        self.bad_words.is_empty()
+    }
+}
+
+/// Backend used to find and censor profane words in user content
+#[allow(clippy::module_name_repetitions)]
+#[async_trait]
+pub trait ProfanityFilter: Debug + Send + Sync {
+    /// Returns the content with every profane word censored, clean content is returned
+    /// unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the backend cannot check the content.
+    async fn check_profanity(&self, content: String) -> Result<Censored, handle_errors::Error>;
+
+    /// Check several pieces of content, e.g. the title and content of a question, returning
+    /// results in the same order. Backends that can check them in one go override this.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the backend cannot check any of the content.
+    async fn check_profanity_batch(
+        &self,
+        contents: Vec<String>,
+    ) -> Result<Vec<Censored>, handle_errors::Error> {
+        future::try_join_all(
+            contents
+                .into_iter()
+                .map(|content| self.check_profanity(content)),
+        )
+        .await
+    }
+
+    /// Runtime metrics of the filter, if it keeps any.
+    fn metrics(&self) -> Option<ProfanityMetrics> {
+        None
+    }
+}
+
+/// Cache statistics of a profanity filter
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct ProfanityMetrics {
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+    /// Share of checks answered from the cache, `0` before the first check
+    pub cache_hit_rate: f64,
+    pub cached_entries: usize,
+    pub cache_capacity: usize,
+}
@@ -0,0 +1,193 @@
+use crate::profanity::{BadWord, Censored, ProfanityFilter};
+use async_trait::async_trait;
+use std::collections::HashSet;
+use std::{fs, io, path::Path};
+
+/// Offline profanity filter that censors whole words found in a local wordlist.
+///
+/// Words are matched case-insensitively on word boundaries, after undoing common leetspeak
+/// substitutions, so `Sh1t` matches `shit` but `class` doesn't match `ass`.
+#[derive(Clone, Debug, Default)]
+pub struct WordlistFilter {
+    words: HashSet<String>,
+}
+
+impl WordlistFilter {
+    #[must_use]
+    pub fn new<I, S>(words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self {
+            words: words
+                .into_iter()
+                .map(|word| normalize(word.as_ref().trim()))
+                .filter(|word| !word.is_empty())
+                .collect(),
+        }
+    }
+
+    /// Load a wordlist with one word per line, lines starting with `#` are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the file cannot be read.
+    pub fn from_file(path: &Path) -> io::Result<Self> {
+        let list = fs::read_to_string(path)?;
+        Ok(Self::new(
+            list.lines()
+                .filter(|line| !line.trim_start().starts_with('#')),
+        ))
+    }
+
+    /// Replace every character of a listed word with `*`.
+    #[must_use]
+    pub fn censor(&self, content: &str) -> Censored {
+        let mut censored = Censored::clean(String::with_capacity(content.len()));
+        let mut word = String::new();
+
+        for c in content.chars() {
+            if is_word_char(c) {
+                word.push(c);
+            } else {
+                self.censor_word(&word, &mut censored);
+                word.clear();
+                censored.content.push(c);
+            }
+        }
+        self.censor_word(&word, &mut censored);
+
+        censored
+    }
+
+    fn censor_word(&self, word: &str, censored: &mut Censored) {
+        // Leetspeak symbols double as punctuation, so also try the word without them on
+        // either end, e.g. `shit!` at the end of a sentence
+        let is_symbol = |c: char| !c.is_alphanumeric();
+        let candidates = [
+            word,
+            word.trim_end_matches(is_symbol),
+            word.trim_start_matches(is_symbol),
+            word.trim_matches(is_symbol),
+        ];
+        for candidate in candidates {
+            if candidate.is_empty() {
+                continue;
+            }
+            let normalized = normalize(candidate);
+            if self.words.contains(&normalized) {
+                let start = word.find(candidate).unwrap_or_default();
+                let end = start + candidate.len();
+                let len = candidate.chars().count();
+                // Censoring keeps the character count, so offsets in the output match the input
+                let offset = censored.content.chars().count() + word[..start].chars().count();
+                censored.content.push_str(&word[..start]);
+                censored.content.push_str(&"*".repeat(len));
+                censored.content.push_str(&word[end..]);
+                censored.bad_words.push(BadWord {
+                    original: candidate.to_owned(),
+                    deviations: deviations(candidate, &normalized),
+                    word: normalized,
+                    info: 0,
+                    replaced_len: i64::try_from(len).unwrap_or(i64::MAX),
+                    start: i64::try_from(offset).unwrap_or(i64::MAX),
+                    end: i64::try_from(offset + len).unwrap_or(i64::MAX),
+                });
+                return;
+            }
+        }
+
+        censored.content.push_str(word);
+    }
+}
+
+#[async_trait]
+impl ProfanityFilter for WordlistFilter {
+    async fn check_profanity(&self, content: String) -> Result<Censored, handle_errors::Error> {
+        Ok(self.censor(&content))
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || unleet(c).is_some()
+}
+
+fn unleet(c: char) -> Option<char> {
+    match c {
+        '@' | '4' => Some('a'),
+        '8' => Some('b'),
+        '3' => Some('e'),
+        '6' | '9' => Some('g'),
+        '1' | '!' | '|' => Some('i'),
+        '0' => Some('o'),
+        '$' | '5' => Some('s'),
+        '7' | '+' => Some('t'),
+        _ => None,
+    }
+}
+
+fn normalize(word: &str) -> String {
+    word.chars()
+        .flat_map(char::to_lowercase)
+        .map(|c| unleet(c).unwrap_or(c))
+        .collect()
+}
+
+/// Number of leetspeak substitutions between the original word and the listed one
+fn deviations(original: &str, normalized: &str) -> i64 {
+    let count = original
+        .chars()
+        .flat_map(char::to_lowercase)
+        .zip(normalized.chars())
+        .filter(|(a, b)| a != b)
+        .count();
+    i64::try_from(count).unwrap_or(i64::MAX)
+}
+
+#[cfg(test)]
+mod wordlist_tests {
+    use super::{ProfanityFilter, WordlistFilter};
+
+    fn filter() -> WordlistFilter {
+        WordlistFilter::new(["shitty", "ass", "  "])
+    }
+
+    #[tokio::test]
+    async fn censor_profane_words() {
+        let censored = filter()
+            .check_profanity("This is a shitty sentence".to_owned())
+            .await
+            .unwrap();
+        assert_eq!(censored.content, "This is a ****** sentence");
+        assert_eq!(censored.bad_words.len(), 1);
+        assert_eq!(censored.bad_words[0].word, "shitty");
+    }
+
+    #[test]
+    fn censor_leetspeak() {
+        let censored = filter().censor("What a $h1tty day");
+        assert_eq!(censored.content, "What a ****** day");
+        assert_eq!(censored.bad_words[0].original, "$h1tty");
+        assert_eq!(censored.bad_words[0].deviations, 2);
+        assert_eq!(
+            (censored.bad_words[0].start, censored.bad_words[0].end),
+            (7, 13)
+        );
+        assert_eq!(filter().censor("Such an @ss!").content, "Such an ***!");
+    }
+
+    #[test]
+    fn match_on_word_boundaries() {
+        let content = "A classic assessment";
+        assert_eq!(filter().censor(content).content, content);
+    }
+
+    #[test]
+    fn keep_clean_content() {
+        let content = "How do I test 3 things at 1 time?";
+        let censored = filter().censor(content);
+        assert_eq!(censored.content, content);
+        assert!(censored.is_clean());
+    }
+}
@@ -0,0 +1,30 @@
+use crate::Store;
+use std::time::Duration;
+use tokio::time::{self, MissedTickBehavior};
+use tracing::{event, Level};
+
+/// Periodically run posts written while the profanity filter was unavailable through the
+/// moderation policy, `batch_size` questions and answers at a time.
+pub async fn run(store: Store, every: Duration, batch_size: i64) {
+    let mut interval = time::interval(every);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+        recheck(&store, batch_size).await;
+    }
+}
+
+async fn recheck(store: &Store, batch_size: i64) {
+    match store.recheck_questions(batch_size).await {
+        Ok(0) => {}
+        Ok(checked) => event!(target: "eroteme", Level::INFO, "rechecked {checked} questions"),
+        Err(e) => event!(target: "eroteme", Level::ERROR, "cannot recheck questions: {e}"),
+    }
+
+    match store.recheck_answers(batch_size).await {
+        Ok(0) => {}
+        Ok(checked) => event!(target: "eroteme", Level::INFO, "rechecked {checked} answers"),
+        Err(e) => event!(target: "eroteme", Level::ERROR, "cannot recheck answers: {e}"),
+    }
+}
@@ -1,7 +1,13 @@
+use crate::types::Role;
 use crate::{routes, Store};
 use warp::{http::Method, reply::Reply, Filter};
 
 pub(crate) async fn build_routes(store: Store) -> impl Filter<Extract = impl Reply> + Clone {
+    let auth = routes::auth(store.clone());
+    let poster = routes::require_verified(store.clone());
+    let moderator = routes::require_role(store.clone(), Role::Moderator);
+    let admin = routes::require_role(store.clone(), Role::Admin);
+    let client_ip = routes::client_ip(store.clone());
     let store_filter = warp::any().map(move || store.clone());
 
     let cors = warp::cors()
@@ -16,11 +22,27 @@ pub(crate) async fn build_routes(store: Store) -> impl Filter<Extract = impl Rep
         .and(store_filter.clone())
         .and_then(routes::get_questions);
 
+    let get_question = warp::get()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and_then(routes::get_question);
+
+    let get_answers = warp::get()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("answers"))
+        .and(warp::path::end())
+        .and(warp::query())
+        .and(store_filter.clone())
+        .and_then(routes::get_answers);
+
     let update_question = warp::put()
         .and(warp::path("questions"))
         .and(warp::path::param::<i32>())
         .and(warp::path::end())
-        .and(routes::authentication::auth())
+        .and(poster.clone())
         .and(store_filter.clone())
         .and(warp::body::json())
         .and_then(routes::update_question);
@@ -29,48 +51,438 @@ pub(crate) async fn build_routes(store: Store) -> impl Filter<Extract = impl Rep
         .and(warp::path("questions"))
         .and(warp::path::param::<i32>())
         .and(warp::path::end())
-        .and(routes::authentication::auth())
+        .and(auth.clone())
         .and(store_filter.clone())
         .and_then(routes::delete_question);
 
-    // TODO: change route to `/questions/:question_id/answers`
     let add_question = warp::post()
         .and(warp::path("questions"))
         .and(warp::path::end())
-        .and(routes::authentication::auth())
+        .and(poster.clone())
         .and(store_filter.clone())
         .and(warp::body::json())
         .and_then(routes::add_question);
 
     let add_answer = warp::post()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
         .and(warp::path("answers"))
         .and(warp::path::end())
-        .and(routes::authentication::auth())
+        .and(poster.clone())
         .and(store_filter.clone())
-        .and(warp::body::form())
+        .and(warp::body::json())
         .and_then(routes::add_answer);
 
+    let update_answer = warp::put()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("answers"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(poster.clone())
+        .and(store_filter.clone())
+        .and(warp::body::json())
+        .and_then(routes::update_answer);
+
+    let delete_answer = warp::delete()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("answers"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and_then(routes::delete_answer);
+
+    let get_question_comments = warp::get()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("comments"))
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and_then(routes::get_question_comments);
+
+    let add_question_comment = warp::post()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("comments"))
+        .and(warp::path::end())
+        .and(poster.clone())
+        .and(store_filter.clone())
+        .and(warp::body::json())
+        .and_then(routes::add_question_comment);
+
+    let get_answer_comments = warp::get()
+        .and(warp::path("answers"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("comments"))
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and_then(routes::get_answer_comments);
+
+    let add_answer_comment = warp::post()
+        .and(warp::path("answers"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("comments"))
+        .and(warp::path::end())
+        .and(poster.clone())
+        .and(store_filter.clone())
+        .and(warp::body::json())
+        .and_then(routes::add_answer_comment);
+
+    let update_comment = warp::put()
+        .and(warp::path("comments"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(poster.clone())
+        .and(store_filter.clone())
+        .and(warp::body::json())
+        .and_then(routes::update_comment);
+
+    let delete_comment = warp::delete()
+        .and(warp::path("comments"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and_then(routes::delete_comment);
+
+    let accept_answer = warp::post()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("accept"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and_then(routes::accept_answer);
+
+    let unaccept_answer = warp::post()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("unaccept"))
+        .and(warp::path::end())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and_then(routes::unaccept_answer);
+
+    let vote_question = warp::put()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("vote"))
+        .and(warp::path::end())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and(warp::body::json())
+        .and_then(routes::vote_question);
+
+    let retract_question_vote = warp::delete()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("vote"))
+        .and(warp::path::end())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and_then(routes::retract_question_vote);
+
+    let vote_answer = warp::put()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("answers"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("vote"))
+        .and(warp::path::end())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and(warp::body::json())
+        .and_then(routes::vote_answer);
+
+    let retract_answer_vote = warp::delete()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("answers"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("vote"))
+        .and(warp::path::end())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and_then(routes::retract_answer_vote);
+
+    let flag_question = warp::post()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("flags"))
+        .and(warp::path::end())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and(warp::body::json())
+        .and_then(routes::flag_question);
+
+    let flag_answer = warp::post()
+        .and(warp::path("answers"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("flags"))
+        .and(warp::path::end())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and(warp::body::json())
+        .and_then(routes::flag_answer);
+
+    let get_flagged_posts = warp::get()
+        .and(warp::path("moderation"))
+        .and(warp::path("flags"))
+        .and(warp::path::end())
+        .and(warp::query())
+        .and(moderator.clone())
+        .and(store_filter.clone())
+        .and_then(routes::get_flagged_posts);
+
+    let get_moderation_queue = warp::get()
+        .and(warp::path("moderation"))
+        .and(warp::path("queue"))
+        .and(warp::path::end())
+        .and(warp::query())
+        .and(moderator.clone())
+        .and(store_filter.clone())
+        .and_then(routes::get_moderation_queue);
+
+    let approve_question = warp::post()
+        .and(warp::path("moderation"))
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("approve"))
+        .and(warp::path::end())
+        .and(moderator.clone())
+        .and(store_filter.clone())
+        .and_then(routes::approve_question);
+
+    let hide_question = warp::post()
+        .and(warp::path("moderation"))
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("hide"))
+        .and(warp::path::end())
+        .and(moderator.clone())
+        .and(store_filter.clone())
+        .and_then(routes::hide_question);
+
+    let remove_question = warp::delete()
+        .and(warp::path("moderation"))
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(moderator.clone())
+        .and(store_filter.clone())
+        .and_then(routes::remove_question);
+
+    let approve_answer = warp::post()
+        .and(warp::path("moderation"))
+        .and(warp::path("answers"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("approve"))
+        .and(warp::path::end())
+        .and(moderator.clone())
+        .and(store_filter.clone())
+        .and_then(routes::approve_answer);
+
+    let hide_answer = warp::post()
+        .and(warp::path("moderation"))
+        .and(warp::path("answers"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("hide"))
+        .and(warp::path::end())
+        .and(moderator.clone())
+        .and(store_filter.clone())
+        .and_then(routes::hide_answer);
+
+    let remove_answer = warp::delete()
+        .and(warp::path("moderation"))
+        .and(warp::path("answers"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(moderator.clone())
+        .and(store_filter.clone())
+        .and_then(routes::remove_answer);
+
+    let set_role = warp::put()
+        .and(warp::path("admin"))
+        .and(warp::path("accounts"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("role"))
+        .and(warp::path::end())
+        .and(admin.clone())
+        .and(store_filter.clone())
+        .and(warp::body::json())
+        .and_then(routes::set_role);
+
+    let get_audit_log = warp::get()
+        .and(warp::path("admin"))
+        .and(warp::path("audit-log"))
+        .and(warp::path::end())
+        .and(warp::query())
+        .and(admin.clone())
+        .and(store_filter.clone())
+        .and_then(routes::get_audit_log);
+
+    let get_metrics = warp::get()
+        .and(warp::path("admin"))
+        .and(warp::path("metrics"))
+        .and(warp::path::end())
+        .and(admin.clone())
+        .and(store_filter.clone())
+        .and_then(routes::get_metrics);
+
+    let search = warp::get()
+        .and(warp::path("search"))
+        .and(warp::path::end())
+        .and(warp::query())
+        .and(store_filter.clone())
+        .and_then(routes::search);
+
+    let get_tags = warp::get()
+        .and(warp::path("tags"))
+        .and(warp::path::end())
+        .and(warp::query())
+        .and(store_filter.clone())
+        .and_then(routes::get_tags);
+
     let registration = warp::post()
         .and(warp::path("registration"))
         .and(warp::path::end())
         .and(store_filter.clone())
+        .and(client_ip.clone())
         .and(warp::body::json())
         .and_then(routes::register);
 
     let login = warp::post()
         .and(warp::path("login"))
         .and(warp::path::end())
-        .and(store_filter)
+        .and(store_filter.clone())
+        .and(client_ip.clone())
         .and(warp::body::json())
         .and_then(routes::login);
 
+    let verify_email = warp::get()
+        .and(warp::path("verify"))
+        .and(warp::path::end())
+        .and(warp::query())
+        .and(store_filter.clone())
+        .and_then(routes::verify_email);
+
+    let resend_verification = warp::post()
+        .and(warp::path("verify"))
+        .and(warp::path("resend"))
+        .and(warp::path::end())
+        .and(auth.clone())
+        .and(client_ip.clone())
+        .and(store_filter.clone())
+        .and_then(routes::resend_verification);
+
+    let forgot_password = warp::post()
+        .and(warp::path("password"))
+        .and(warp::path("forgot"))
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and(client_ip.clone())
+        .and(warp::body::json())
+        .and_then(routes::forgot_password);
+
+    let reset_password = warp::post()
+        .and(warp::path("password"))
+        .and(warp::path("reset"))
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and(client_ip.clone())
+        .and(warp::body::json())
+        .and_then(routes::reset_password);
+
+    let change_password = warp::put()
+        .and(warp::path("account"))
+        .and(warp::path("password"))
+        .and(warp::path::end())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and(warp::body::json())
+        .and_then(routes::change_password);
+
+    let delete_account = warp::delete()
+        .and(warp::path("account"))
+        .and(warp::path::end())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and(warp::body::json())
+        .and_then(routes::delete_account);
+
+    let refresh = warp::post()
+        .and(warp::path("token"))
+        .and(warp::path("refresh"))
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and(warp::body::json())
+        .and_then(routes::refresh);
+
+    let logout = warp::post()
+        .and(warp::path("logout"))
+        .and(warp::path::end())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and_then(routes::logout);
+
+    let logout_everywhere = warp::post()
+        .and(warp::path("logout"))
+        .and(warp::path("all"))
+        .and(warp::path::end())
+        .and(auth)
+        .and(store_filter)
+        .and_then(routes::logout_everywhere);
+
     get_questions
+        .or(get_question)
+        .or(get_answers)
         .or(update_question)
         .or(add_question)
         .or(delete_question)
         .or(add_answer)
+        .or(update_answer)
+        .or(delete_answer)
+        .or(get_question_comments)
+        .or(add_question_comment)
+        .or(get_answer_comments)
+        .or(add_answer_comment)
+        .or(update_comment)
+        .or(delete_comment)
+        .or(accept_answer)
+        .or(unaccept_answer)
+        .or(vote_question)
+        .or(retract_question_vote)
+        .or(vote_answer)
+        .or(retract_answer_vote)
+        .or(flag_question)
+        .or(flag_answer)
+        .or(get_flagged_posts)
+        .or(get_moderation_queue)
+        .or(approve_question)
+        .or(hide_question)
+        .or(remove_question)
+        .or(approve_answer)
+        .or(hide_answer)
+        .or(remove_answer)
+        .or(set_role)
+        .or(get_audit_log)
+        .or(get_metrics)
+        .or(search)
+        .or(get_tags)
         .or(registration)
         .or(login)
+        .or(verify_email)
+        .or(resend_verification)
+        .or(forgot_password)
+        .or(reset_password)
+        .or(change_password)
+        .or(delete_account)
+        .or(refresh)
+        .or(logout)
+        .or(logout_everywhere)
         .with(cors)
         .with(warp::trace::request())
         .recover(handle_errors::return_error)
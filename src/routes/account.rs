@@ -0,0 +1,72 @@
+use crate::routes::authentication::{spawn_hash, verify_password};
+use crate::store::Store;
+use crate::types::{AccountDeletion, AccountId, PasswordChange, Session};
+use tracing::{event, Level};
+use warp::http::StatusCode;
+
+/// Set a new password, other sessions of the account are logged out.
+///
+/// # Errors
+///
+/// Will return `Err` if the current password is wrong, the new one doesn't follow the
+/// password policy or the database query fails.
+pub async fn change_password(
+    session: Session,
+    store: Store,
+    change: PasswordChange,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    check_password(&store, &session.account_id, &change.current_password).await?;
+    store.accounts.check_password(&change.new_password)?;
+
+    let password = spawn_hash(change.new_password, store.accounts.argon2).await;
+    store
+        .change_password(&session.account_id, &password, session.session_id)
+        .await?;
+
+    Ok(warp::reply::with_status(
+        "password changed".to_owned(),
+        StatusCode::OK,
+    ))
+}
+
+/// Delete the logged in account, anonymizing or deleting what it posted.
+///
+/// # Errors
+///
+/// Will return `Err` if the password is wrong or the database query fails.
+pub async fn delete_account(
+    session: Session,
+    store: Store,
+    deletion: AccountDeletion,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    check_password(&store, &session.account_id, &deletion.password).await?;
+
+    store
+        .delete_account(&session.account_id, deletion.content)
+        .await?;
+    event!(
+        target: "eroteme",
+        Level::INFO,
+        "deleted {:?}, {:?} its content",
+        session.account_id,
+        deletion.content
+    );
+
+    Ok(warp::reply::with_status(
+        "account deleted".to_owned(),
+        StatusCode::OK,
+    ))
+}
+
+async fn check_password(
+    store: &Store,
+    account_id: &AccountId,
+    password: &str,
+) -> Result<(), handle_errors::Error> {
+    let account = store.get_account_by_id(account_id).await?;
+    match verify_password(account.password, password.to_owned()).await {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(handle_errors::Error::WrongPassword),
+        Err(e) => Err(handle_errors::Error::ArgonLibraryError(e)),
+    }
+}
@@ -0,0 +1,74 @@
+use crate::store::Store;
+use crate::types::pagination::{self, Pagination};
+use crate::types::{NewRole, Session};
+use std::collections::HashMap;
+use tracing::{event, instrument, Level};
+use warp::http::StatusCode;
+
+/// # Errors
+///
+/// Will return `Err` if the account does not exist or the database query fails.
+pub async fn set_role(
+    account_id: i32,
+    session: Session,
+    store: Store,
+    new_role: NewRole,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    // Admins can't lock themselves out, another admin has to demote them
+    if account_id == session.account_id.0 {
+        return Err(warp::reject::custom(
+            handle_errors::Error::InvalidParameter("cannot change your own role".to_owned()),
+        ));
+    }
+
+    let role = new_role.role;
+    match store
+        .set_role(account_id, new_role, &session.account_id)
+        .await
+    {
+        Ok(()) => Ok(warp::reply::with_status(
+            format!("Account {account_id} is now {}", role.as_str()),
+            StatusCode::OK,
+        )),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+/// # Errors
+///
+/// Never returns `Err`, the signature matches the other route handlers.
+#[instrument]
+pub async fn get_metrics(
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    Ok(warp::reply::json(&serde_json::json!({
+        "profanity_filter": store.profanity_metrics(),
+    })))
+}
+
+/// # Errors
+///
+/// Will return `Err` if the pagination parameters are invalid or the database query fails.
+#[instrument]
+pub async fn get_audit_log(
+    params: HashMap<String, String>,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    event!(target: "eroteme", Level::INFO, "querying audit log");
+
+    let pagination = if params.is_empty() {
+        Pagination::default()
+    } else {
+        pagination::extract_pagination(&params)?
+    };
+
+    match store
+        .get_audit_log(pagination.limit, pagination.offset)
+        .await
+    {
+        Ok(res) => Ok(warp::reply::json(&res)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
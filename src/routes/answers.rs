@@ -1,29 +1,148 @@
-use crate::types::{NewAnswer, Session};
-use crate::{check_profanity, Store};
-use warp::http::StatusCode;
+use crate::types::pagination::{self, Pagination};
+use crate::types::query;
+use crate::types::{ModeratedField, NewAnswer, Session};
+use crate::Store;
+use std::collections::HashMap;
+use tracing::{event, instrument, Level};
+use warp::http::{header, StatusCode};
+
+/// # Errors
+///
+/// Will return `Err` if the question does not exist, the sort or pagination parameters are
+/// invalid or the database query fails.
+#[instrument]
+pub async fn get_answers(
+    question_id: i32,
+    params: HashMap<String, String>,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    event!(target: "eroteme", Level::INFO, "querying answers for question {question_id}");
+
+    let sort = query::extract_answer_sort(&params)?;
+
+    let pagination = if params.contains_key("limit") || params.contains_key("offset") {
+        event!(Level::INFO, pagination = true);
+        pagination::extract_pagination(&params)?
+    } else {
+        Pagination::default()
+    };
+
+    store.clone().get_question(question_id).await?;
+
+    match store
+        .get_answers(question_id, sort, pagination.limit, pagination.offset)
+        .await
+    {
+        Ok(res) => Ok(warp::reply::json(&res)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
 
 /// # Errors
 ///
 /// Will return `Err` if the Warp filter fails to match the route
 pub async fn add_answer(
+    question_id: i32,
     session: Session,
     store: Store,
     new_answer: NewAnswer,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     let account_id = session.account_id;
 
-    let content = match check_profanity(new_answer.content).await {
+    store.clone().get_question(question_id).await?;
+
+    let moderated = match store
+        .moderate(ModeratedField::Content, new_answer.content)
+        .await
+    {
         Ok(res) => res,
         Err(e) => return Err(warp::reject::custom(e)),
     };
 
     let answer = NewAnswer {
-        content,
-        question_id: new_answer.question_id,
+        content: moderated.content,
     };
 
-    match store.add_answer(answer, account_id).await {
-        Ok(_) => Ok(warp::reply::with_status("answer added", StatusCode::OK)),
+    match store
+        .add_answer(answer, moderated.status, question_id, account_id)
+        .await
+    {
+        Ok(answer) => {
+            let location = format!("/questions/{}/answers/{}", question_id, answer.id.0);
+            Ok(warp::reply::with_header(
+                warp::reply::with_status(warp::reply::json(&answer), StatusCode::CREATED),
+                header::LOCATION,
+                location,
+            ))
+        }
         Err(e) => Err(warp::reject::custom(e)),
     }
 }
+
+/// # Errors
+///
+/// Will return `Err` if the Warp filter fails to match the route
+pub async fn update_answer(
+    question_id: i32,
+    answer_id: i32,
+    session: Session,
+    store: Store,
+    answer: NewAnswer,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    if store
+        .is_answer_owner(answer_id, question_id, &account_id)
+        .await?
+    {
+        let moderated = match store
+            .moderate(ModeratedField::Content, answer.content)
+            .await
+        {
+            Ok(res) => res,
+            Err(e) => return Err(warp::reject::custom(e)),
+        };
+
+        let answer = NewAnswer {
+            content: moderated.content,
+        };
+
+        match store
+            .update_answer(answer, moderated.status, answer_id, question_id, account_id)
+            .await
+        {
+            Ok(res) => Ok(warp::reply::json(&res)),
+            Err(e) => Err(warp::reject::custom(e)),
+        }
+    } else {
+        Err(warp::reject::custom(handle_errors::Error::Unauthorized))
+    }
+}
+
+/// # Errors
+///
+/// Will return `Err` if the Warp filter fails to match the route
+pub async fn delete_answer(
+    question_id: i32,
+    answer_id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    if store
+        .is_answer_owner(answer_id, question_id, &account_id)
+        .await?
+    {
+        match store
+            .delete_answer(answer_id, question_id, &account_id)
+            .await
+        {
+            Ok(_) => Ok(warp::reply::with_status(
+                format!("Answer {answer_id} deleted"),
+                StatusCode::OK,
+            )),
+            Err(e) => Err(warp::reject::custom(e)),
+        }
+    } else {
+        Err(warp::reject::custom(handle_errors::Error::Unauthorized))
+    }
+}
@@ -1,139 +1,646 @@
+use crate::keyring::Keyring;
+use crate::limits;
+use crate::mailer::Email;
 use crate::store::Store;
-use crate::types::{Account, AccountId, Session};
-use argon2::{self, Config};
+use crate::types::account;
+use crate::types::{
+    Account, AccountId, Argon2Params, RefreshToken, Role, Session, SessionId, Tokens,
+};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
 use chrono::prelude::*;
 use rand::Rng;
-use std::{env, future};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::future;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::OnceLock;
+use tracing::{event, Level};
+use warp::http::StatusCode;
 use warp::Filter;
 
+/// Access tokens are short lived, a stolen one is only useful for this long
+const ACCESS_TOKEN_MINUTES: i64 = 15;
+/// A session ends if it isn't refreshed for this long
+const SESSION_DAYS: i32 = 30;
+
+/// Verification links stay valid for this long
+const VERIFICATION_HOURS: i64 = 24;
+/// Claim telling verification tokens apart from access tokens encrypted with the same key
+const VERIFY_EMAIL_PURPOSE: &str = "verify_email";
+
+/// Names the routes are rate limited under, each has its own limits
+const LOGIN: &str = "login";
+const REGISTRATION: &str = "registration";
+const VERIFY_RESEND: &str = "verify_resend";
+
+/// Checked against the password when a login names an unknown email, so it takes as long
+/// as a wrong password
+static DUMMY_HASH: OnceLock<String> = OnceLock::new();
+
+/// Create an account and email a link to verify its address.
+///
 /// # Errors
 ///
-/// Will return `Err` if creating the account in the database fails.
-pub async fn register(store: Store, account: Account) -> Result<impl warp::Reply, warp::Rejection> {
-    let hashed_password = hash_password(account.password.as_bytes());
+/// Will return `Err` if the client or email is rate limited, the email address is invalid,
+/// the password doesn't follow the password policy or creating the account in the database
+/// fails.
+pub async fn register(
+    store: Store,
+    ip: Option<IpAddr>,
+    account: Account,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    store
+        .limits
+        .check(REGISTRATION, ip, &limits::limit_key(&account.email))?;
+    let email = account::validate_email(&account.email)?;
+    store.accounts.check_password(&account.password)?;
+    let hashed_password = spawn_hash(account.password, store.accounts.argon2).await;
 
     let account = Account {
         id: account.id,
-        email: account.email,
+        email: email.clone(),
         password: hashed_password,
+        role: Role::User,
+        verified: false,
     };
 
-    match store.add_account(account).await {
-        Ok(_) => Ok(warp::reply::json(&"account added".to_string())),
+    match store.clone().add_account(account).await {
+        Ok(account_id) => {
+            // The account exists either way, a failed email is logged rather than failing
+            // the registration, the link can be sent again with `/verify/resend`
+            if let Err(e) = send_verification(&store, &account_id, email).await {
+                event!(Level::ERROR, "cannot send verification email: {e}");
+            }
+            Ok(warp::reply::json(&"account added".to_string()))
+        }
         Err(e) => Err(warp::reject::custom(e)),
     }
 }
 
+/// The link names the account as well as the address, so it can't verify another account
+/// registered with the same address after this one is deleted.
+async fn send_verification(
+    store: &Store,
+    account_id: &AccountId,
+    email: String,
+) -> Result<(), handle_errors::Error> {
+    let expires_at = Utc::now() + chrono::Duration::hours(VERIFICATION_HOURS);
+    let token = encrypt(
+        &store.keyring,
+        &expires_at,
+        &[
+            ("purpose", serde_json::json!(VERIFY_EMAIL_PURPOSE)),
+            ("account_id", serde_json::json!(account_id)),
+            ("email", serde_json::json!(email)),
+        ],
+    )?;
+
+    store
+        .mailer
+        .send(Email {
+            to: email,
+            subject: "Verify your email address".to_owned(),
+            body: format!(
+                "Open this link within {VERIFICATION_HOURS} hours to verify your email \
+                address:\n\n{}/verify?token={token}",
+                store.accounts.public_url.trim_end_matches('/')
+            ),
+        })
+        .await
+}
+
+/// Mark an email address as verified with the token from the verification email.
+///
+/// # Errors
+///
+/// Will return `Err` if the token is missing, invalid or expired, or the account no longer
+/// exists.
+pub async fn verify_email(
+    params: HashMap<String, String>,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let token = params
+        .get("token")
+        .ok_or(handle_errors::Error::MissingParameters)?;
+
+    let claims = decrypt(&store.keyring, token)?;
+    let account_id = claims
+        .get("account_id")
+        .and_then(|id| serde_json::from_value::<AccountId>(id.clone()).ok());
+    let (account_id, email) = match (claims.get("purpose"), account_id, claims.get("email")) {
+        (Some(purpose), Some(account_id), Some(serde_json::Value::String(email)))
+            if purpose == VERIFY_EMAIL_PURPOSE =>
+        {
+            (account_id, email)
+        }
+        _ => {
+            return Err(warp::reject::custom(
+                handle_errors::Error::CannotDecryptToken,
+            ))
+        }
+    };
+
+    if store.verify_email(&account_id, email).await? {
+        Ok(warp::reply::with_status(
+            "email verified".to_owned(),
+            StatusCode::OK,
+        ))
+    } else {
+        Err(warp::reject::custom(handle_errors::Error::NotFound))
+    }
+}
+
+/// Send the verification link of the logged in account again, for when the first email
+/// didn't arrive or the link expired.
+///
+/// # Errors
+///
+/// Will return `Err` if the account or client is rate limited, the account no longer exists
+/// or the email cannot be sent.
+pub async fn resend_verification(
+    session: Session,
+    ip: Option<IpAddr>,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account = store.get_account_by_id(&session.account_id).await?;
+    store
+        .limits
+        .check(VERIFY_RESEND, ip, &limits::limit_key(&account.email))?;
+
+    if account.verified {
+        return Ok(warp::reply::with_status(
+            "email already verified".to_owned(),
+            StatusCode::OK,
+        ));
+    }
+
+    send_verification(&store, &session.account_id, account.email).await?;
+    Ok(warp::reply::with_status(
+        "verification email sent".to_owned(),
+        StatusCode::ACCEPTED,
+    ))
+}
+
 /// # Panics
 ///
 /// Will panic if `hash_encoded` fails to hash password.
 #[must_use]
-pub fn hash_password(password: &[u8]) -> String {
+pub fn hash_password(password: &[u8], params: &Argon2Params) -> String {
     let salt = rand::thread_rng().gen::<[u8; 32]>();
-    let config = Config::default();
-    argon2::hash_encoded(password, &salt, &config).expect("failed to hash password")
+    argon2::hash_encoded(password, &salt, &params.config()).expect("failed to hash password")
 }
 
 /// # Errors
 ///
-/// Will return `Err` if the wrong username/password combination is used or if the `argon2`
-/// library fails to verify the hashed password.
+/// Will return `Err` if the client or email is rate limited, the email is locked out after
+/// too many wrong passwords, the wrong username/password combination is used, if the
+/// `argon2` library fails to verify the hashed password or the session cannot be stored. A
+/// hash made with weaker argon2 parameters than configured is replaced on success.
 ///
 /// # Panics
 ///
 /// Will panic if account id cannot be found in the database.
-pub async fn login(store: Store, login: Account) -> Result<impl warp::Reply, warp::Rejection> {
-    match store.get_account(login.email).await {
-        Ok(account) => match verify_password(&account.password, login.password.as_bytes()) {
-            Ok(verified) => {
-                if verified {
-                    Ok(warp::reply::json(&issue_token(
-                        &account.id.expect("id not found"),
-                    )))
-                } else {
-                    Err(warp::reject::custom(handle_errors::Error::WrongPassword))
-                }
-            }
-            Err(e) => Err(warp::reject::custom(
-                handle_errors::Error::ArgonLibraryError(e),
-            )),
-        },
-        Err(e) => Err(warp::reject::custom(e)),
+pub async fn login(
+    store: Store,
+    ip: Option<IpAddr>,
+    login: Account,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let key = limits::limit_key(&login.email);
+    store.limits.check(LOGIN, ip, &key)?;
+    store.limits.lockout.check(&key)?;
+
+    // An unknown email goes through the same argon2 work as a wrong password, so the
+    // response doesn't tell which emails have an account
+    let account = store.clone().get_account(login.email).await?;
+    let hash = match &account {
+        Some(account) => account.password.clone(),
+        None => dummy_hash(store.accounts.argon2).await,
+    };
+    let verified = verify_password(hash, login.password.clone())
+        .await
+        .map_err(handle_errors::Error::ArgonLibraryError)?;
+
+    let Some(account) = account.filter(|_| verified) else {
+        store.limits.lockout.record_failure(&key);
+        return Err(warp::reject::custom(handle_errors::Error::WrongPassword));
+    };
+    store.limits.lockout.clear(&key);
+
+    let account_id = account.id.expect("id not found");
+    if store.accounts.argon2.needs_rehash(&account.password) {
+        rehash(&store, &account_id, &account.password, login.password).await;
     }
+    let (refresh_token, hash) = random_token();
+    let session_id = store.add_session(&account_id, &hash, SESSION_DAYS).await?;
+    Ok(warp::reply::json(&tokens(
+        &store.keyring,
+        &account_id,
+        account.role,
+        session_id,
+        refresh_token,
+    )?))
 }
 
+/// Exchange a refresh token for a new access and refresh token. The role is read from the
+/// account, so a changed role takes effect at the next refresh.
+///
 /// # Errors
 ///
-/// Will return `Err` if decryption of token fails
+/// Will return `Err` if the refresh token is unknown, expired, revoked or was already used,
+/// or the database query fails.
+pub async fn refresh(
+    store: Store,
+    token: RefreshToken,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let (refresh_token, hash) = random_token();
+
+    match store
+        .rotate_session(&hash_token(&token.refresh_token), &hash, SESSION_DAYS)
+        .await?
+    {
+        Some((session_id, account_id, role)) => Ok(warp::reply::json(&tokens(
+            &store.keyring,
+            &account_id,
+            role,
+            session_id,
+            refresh_token,
+        )?)),
+        None => Err(warp::reject::custom(handle_errors::Error::InvalidSession)),
+    }
+}
+
+/// Revoke the session the request was made with.
 ///
-/// # Panics
+/// # Errors
 ///
-/// Will panic if `PASETO_KEY` env var is not set.
-pub fn verify_token(token: &str) -> Result<Session, handle_errors::Error> {
-    let key = env::var("PASETO_KEY").expect("PASETO_KEY env var not set");
+/// Will return `Err` if the database query fails.
+pub async fn logout(session: Session, store: Store) -> Result<impl warp::Reply, warp::Rejection> {
+    store
+        .revoke_session(session.session_id, &session.account_id)
+        .await?;
 
-    let token = paseto::tokens::validate_local_token(
+    Ok(warp::reply::with_status(
+        "logged out".to_owned(),
+        StatusCode::OK,
+    ))
+}
+
+/// Revoke every session of the account, on every device.
+///
+/// # Errors
+///
+/// Will return `Err` if the database query fails.
+pub async fn logout_everywhere(
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let revoked = store.revoke_sessions(&session.account_id).await?;
+
+    Ok(warp::reply::with_status(
+        format!("logged out of {revoked} sessions"),
+        StatusCode::OK,
+    ))
+}
+
+/// Decrypt an access token.
+///
+/// # Errors
+///
+/// Will return `Err` if the key is not in the keyring or decryption of token fails
+pub fn verify_token(keyring: &Keyring, token: &str) -> Result<Session, handle_errors::Error> {
+    serde_json::from_value::<Session>(decrypt(keyring, token)?)
+        .map_err(|_| handle_errors::Error::CannotDecryptToken)
+}
+
+/// Decrypt a token with the key named in its footer and return its claims.
+fn decrypt(keyring: &Keyring, token: &str) -> Result<serde_json::Value, handle_errors::Error> {
+    let key = keyring
+        .find(token)
+        .ok_or(handle_errors::Error::CannotDecryptToken)?;
+
+    paseto::tokens::validate_local_token(
         token,
-        None,
-        key.as_bytes(),
+        Some(&key.footer()),
+        &key.key,
         &paseto::tokens::TimeBackend::Chrono,
     )
-    .map_err(|_| handle_errors::Error::CannotDecryptToken)?;
+    .map_err(|_| handle_errors::Error::CannotDecryptToken)
+}
+
+/// Encrypt claims with the current key of the keyring, naming the key in the footer.
+///
+/// # Errors
+///
+/// Will return `Err` if the keyring is empty or the token cannot be built.
+fn encrypt(
+    keyring: &Keyring,
+    expires_at: &DateTime<Utc>,
+    claims: &[(&str, serde_json::Value)],
+) -> Result<String, handle_errors::Error> {
+    let key = keyring.current().ok_or_else(|| {
+        handle_errors::Error::ConfigError("no Paseto key to issue tokens with".to_owned())
+    })?;
+    let footer = key.footer();
 
-    serde_json::from_value::<Session>(token).map_err(|_| handle_errors::Error::CannotDecryptToken)
+    let mut builder = paseto::tokens::PasetoBuilder::new();
+    // Every setter borrows the builder for its whole lifetime, so the returned reference
+    // is carried through the loop instead of borrowing `builder` again
+    let mut builder = builder
+        .set_encryption_key(&key.key)
+        .set_footer(&footer)
+        .set_expiration(expires_at);
+    for (name, value) in claims {
+        builder = builder.set_claim(name, value.clone());
+    }
+
+    builder
+        .build()
+        .map_err(|e| handle_errors::Error::ConfigError(format!("cannot build token: {e}")))
+}
+
+/// Upgrade a password hash, a failure is logged and the old hash keeps working.
+async fn rehash(store: &Store, account_id: &AccountId, old_hash: &str, password: String) {
+    let new_hash = spawn_hash(password, store.accounts.argon2).await;
+    if let Err(e) = store.rehash_password(account_id, old_hash, &new_hash).await {
+        event!(
+            Level::ERROR,
+            "cannot rehash password of {account_id:?}: {e}"
+        );
+    }
 }
 
-fn verify_password(hash: &str, password: &[u8]) -> Result<bool, argon2::Error> {
-    argon2::verify_encoded(hash, password)
+/// Argon2 is slow on purpose, hashing runs on the blocking pool so it doesn't hold up other
+/// requests.
+pub(crate) async fn spawn_hash(password: String, params: Argon2Params) -> String {
+    tokio::task::spawn_blocking(move || hash_password(password.as_bytes(), &params))
+        .await
+        .expect("password hashing panicked")
 }
 
-/// # Panics
-///
-/// Will panic if `PASETO_KEY` env var is not set.
-fn issue_token(account_id: &AccountId) -> String {
-    let key = env::var("PASETO_KEY").expect("PASETO_KEY env var not set");
+pub(crate) async fn verify_password(hash: String, password: String) -> Result<bool, argon2::Error> {
+    tokio::task::spawn_blocking(move || argon2::verify_encoded(&hash, password.as_bytes()))
+        .await
+        .expect("password verification panicked")
+}
+
+async fn dummy_hash(params: Argon2Params) -> String {
+    if let Some(hash) = DUMMY_HASH.get() {
+        return hash.clone();
+    }
+    let hash = spawn_hash(random_token().0, params).await;
+    DUMMY_HASH.get_or_init(|| hash).clone()
+}
 
+/// # Errors
+///
+/// Will return `Err` if the keyring is empty or the token cannot be built.
+fn issue_token(
+    keyring: &Keyring,
+    account_id: &AccountId,
+    role: Role,
+    session_id: SessionId,
+) -> Result<(String, DateTime<Utc>), handle_errors::Error> {
     let current_date_time = Utc::now();
-    let dt = current_date_time + chrono::Duration::days(1);
+    let dt = current_date_time + chrono::Duration::minutes(ACCESS_TOKEN_MINUTES);
 
-    paseto::tokens::PasetoBuilder::new()
-        .set_encryption_key(&Vec::from(key.as_bytes()))
-        .set_expiration(&dt)
-        .set_claim("account_id", serde_json::json!(account_id))
-        .build()
-        .expect("failed to construct paseto token with builder")
+    let token = encrypt(
+        keyring,
+        &dt,
+        &[
+            ("account_id", serde_json::json!(account_id)),
+            ("role", serde_json::json!(role)),
+            ("session_id", serde_json::json!(session_id)),
+        ],
+    )?;
+
+    Ok((token, dt))
 }
 
+fn tokens(
+    keyring: &Keyring,
+    account_id: &AccountId,
+    role: Role,
+    session_id: SessionId,
+    refresh_token: String,
+) -> Result<Tokens, handle_errors::Error> {
+    let (access_token, expires_at) = issue_token(keyring, account_id, role, session_id)?;
+    Ok(Tokens {
+        access_token,
+        refresh_token,
+        expires_at,
+    })
+}
+
+/// A random refresh or reset token and the hash it is stored under.
+pub(crate) fn random_token() -> (String, Vec<u8>) {
+    let token = URL_SAFE_NO_PAD.encode(rand::thread_rng().gen::<[u8; 32]>());
+    let hash = hash_token(&token);
+    (token, hash)
+}
+
+pub(crate) fn hash_token(token: &str) -> Vec<u8> {
+    Sha256::digest(token.as_bytes()).to_vec()
+}
+
+/// Address of the client, the last `X-Forwarded-For` entry when the deployment trusts it,
+/// since that's the one added by the proxy.
 #[must_use]
-pub fn auth() -> impl Filter<Extract = (Session,), Error = warp::Rejection> + Clone {
-    warp::header::<String>("Authorization").and_then(|token: String| {
-        let Ok(token) = verify_token(&token) else {
-            return future::ready(Err(warp::reject::custom(
-                handle_errors::Error::Unauthorized,
-            )));
-        };
-
-        future::ready(Ok(token))
+pub fn client_ip(
+    store: Store,
+) -> impl Filter<Extract = (Option<IpAddr>,), Error = warp::Rejection> + Clone {
+    warp::addr::remote()
+        .and(warp::header::optional::<String>("x-forwarded-for"))
+        .map(
+            move |remote: Option<SocketAddr>, forwarded: Option<String>| {
+                forwarded
+                    .filter(|_| store.limits.trust_forwarded_for)
+                    .and_then(|forwarded| forwarded.rsplit(',').next()?.trim().parse().ok())
+                    .or_else(|| remote.map(|remote| remote.ip()))
+            },
+        )
+}
+
+/// Accepts requests with a valid access token whose session hasn't been logged out. The
+/// session carries the current role of the account rather than the one in the token.
+#[must_use]
+pub fn auth(store: Store) -> impl Filter<Extract = (Session,), Error = warp::Rejection> + Clone {
+    warp::header::<String>("Authorization").and_then(move |token: String| {
+        let store = store.clone();
+        async move {
+            let Ok(session) = verify_token(&store.keyring, &token) else {
+                return Err(warp::reject::custom(handle_errors::Error::Unauthorized));
+            };
+
+            match store.active_session_role(session.session_id).await? {
+                Some(role) => Ok(Session { role, ..session }),
+                None => Err(warp::reject::custom(handle_errors::Error::InvalidSession)),
+            }
+        }
     })
 }
 
+/// Like `auth`, but also rejects accounts that haven't verified their email address when
+/// the deployment requires it.
+#[must_use]
+pub fn require_verified(
+    store: Store,
+) -> impl Filter<Extract = (Session,), Error = warp::Rejection> + Clone {
+    auth(store.clone()).and_then(move |session: Session| {
+        let store = store.clone();
+        async move {
+            if !store.accounts.require_verified || store.is_verified(&session.account_id).await? {
+                Ok(session)
+            } else {
+                Err(warp::reject::custom(handle_errors::Error::EmailNotVerified))
+            }
+        }
+    })
+}
+
+/// Like `auth`, but also rejects sessions below the given role. The role is read from the
+/// account on every request, so a demoted account loses its privileges right away.
+#[must_use]
+pub fn require_role(
+    store: Store,
+    role: Role,
+) -> impl Filter<Extract = (Session,), Error = warp::Rejection> + Clone {
+    auth(store).and_then(move |session: Session| future::ready(check_role(session, role)))
+}
+
+fn check_role(session: Session, role: Role) -> Result<Session, warp::Rejection> {
+    if session.role < role {
+        return Err(warp::reject::custom(handle_errors::Error::Forbidden));
+    }
+
+    Ok(session)
+}
+
 #[cfg(test)]
 mod authentication_tests {
-    use super::{auth, env, issue_token, AccountId};
+    use super::{auth, check_role, issue_token, verify_token, AccountId, Keyring, Role, SessionId};
+    use super::{decrypt, encrypt, hash_token, random_token};
+    use crate::keyring::PasetoKey;
+    use crate::store::Store;
+    use handle_errors::Error;
+
+    fn keyring() -> Keyring {
+        Keyring::new(
+            PasetoKey::new("1", "RANDOM WORDS WINTER MACINTOSH PC").unwrap(),
+            Vec::new(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn post_questions_auth() {
+        let keyring = keyring();
+        let (token, _) = issue_token(&keyring, &AccountId(3), Role::User, SessionId(7)).unwrap();
+
+        let session = verify_token(&keyring, &token).unwrap();
+
+        assert_eq!(session.account_id, AccountId(3));
+        assert_eq!(session.session_id, SessionId(7));
+    }
 
     #[tokio::test]
-    async fn post_questions_auth() {
-        unsafe {
-            env::set_var("PASETO_KEY", "RANDOM WORDS WINTER MACINTOSH PC");
+    async fn auth_rejects_invalid_tokens() {
+        let store = Store::lazy();
+
+        assert!(warp::test::request()
+            .filter(&auth(store.clone()))
+            .await
+            .is_err());
+
+        let old = Keyring::new(
+            PasetoKey::new("2", "ANOTHER KEY OF THIRTY TWO BYTES!").unwrap(),
+            Vec::new(),
+        )
+        .unwrap();
+        let (token, _) = issue_token(&old, &AccountId(3), Role::Admin, SessionId(1)).unwrap();
+        for token in ["not a token", token.as_str()] {
+            let rejection = warp::test::request()
+                .header("Authorization", token)
+                .filter(&auth(store.clone()))
+                .await
+                .unwrap_err();
+            assert!(matches!(
+                rejection.find::<Error>(),
+                Some(Error::Unauthorized)
+            ));
         }
-        let token = issue_token(&AccountId(3));
+    }
+
+    #[test]
+    fn require_moderator_role() {
+        let keyring = keyring();
 
-        let filter = auth();
+        let (user_token, _) =
+            issue_token(&keyring, &AccountId(3), Role::User, SessionId(1)).unwrap();
+        let user = verify_token(&keyring, &user_token).unwrap();
+        assert!(check_role(user, Role::Moderator).is_err());
+
+        let (admin_token, _) =
+            issue_token(&keyring, &AccountId(4), Role::Admin, SessionId(2)).unwrap();
+        let admin = verify_token(&keyring, &admin_token).unwrap();
+        assert_eq!(
+            check_role(admin, Role::Moderator).unwrap().role,
+            Role::Admin
+        );
+    }
+
+    #[test]
+    fn verify_tokens_of_previous_keys() {
+        let old = keyring();
+        let (token, _) = issue_token(&old, &AccountId(3), Role::User, SessionId(1)).unwrap();
+
+        let rotated = Keyring::new(
+            PasetoKey::new("2", "ANOTHER KEY OF THIRTY TWO BYTES!").unwrap(),
+            vec![PasetoKey::new("1", "RANDOM WORDS WINTER MACINTOSH PC").unwrap()],
+        )
+        .unwrap();
+        assert_eq!(
+            verify_token(&rotated, &token).unwrap().account_id,
+            AccountId(3)
+        );
+
+        let retired = Keyring::new(
+            PasetoKey::new("2", "ANOTHER KEY OF THIRTY TWO BYTES!").unwrap(),
+            Vec::new(),
+        )
+        .unwrap();
+        assert!(verify_token(&retired, &token).is_err());
+    }
+
+    #[test]
+    fn access_tokens_are_not_verification_tokens() {
+        let keyring = keyring();
+        let expires_at = chrono::Utc::now() + chrono::Duration::hours(1);
+        let token = encrypt(
+            &keyring,
+            &expires_at,
+            &[
+                ("purpose", serde_json::json!("verify_email")),
+                ("account_id", serde_json::json!(AccountId(1))),
+                ("email", serde_json::json!("test@email.com")),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            decrypt(&keyring, &token).unwrap()["email"],
+            "test@email.com"
+        );
+        assert!(verify_token(&keyring, &token).is_err());
+    }
 
-        let res = warp::test::request()
-            .header("Authorization", token)
-            .filter(&filter);
+    #[test]
+    fn refresh_tokens_are_stored_hashed() {
+        let (token, hash) = random_token();
+        let (other, _) = random_token();
 
-        assert_eq!(res.await.unwrap().account_id, AccountId(3));
+        assert_ne!(token, other);
+        assert_eq!(hash, hash_token(&token));
+        assert_ne!(hash, token.as_bytes());
     }
 }
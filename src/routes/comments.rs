@@ -0,0 +1,170 @@
+use crate::types::{CommentTarget, NewComment, Session};
+use crate::Store;
+use tracing::{event, instrument, Level};
+use warp::http::StatusCode;
+
+/// # Errors
+///
+/// Will return `Err` if the question does not exist or the database query fails.
+pub async fn get_question_comments(
+    question_id: i32,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    get_comments(CommentTarget::Question(question_id), store).await
+}
+
+/// # Errors
+///
+/// Will return `Err` if the answer does not exist or the database query fails.
+pub async fn get_answer_comments(
+    answer_id: i32,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    get_comments(CommentTarget::Answer(answer_id), store).await
+}
+
+/// # Errors
+///
+/// Will return `Err` if the question or the parent comment does not exist, the profanity
+/// check fails or the database query fails.
+pub async fn add_question_comment(
+    question_id: i32,
+    session: Session,
+    store: Store,
+    new_comment: NewComment,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    add_comment(
+        CommentTarget::Question(question_id),
+        session,
+        store,
+        new_comment,
+    )
+    .await
+}
+
+/// # Errors
+///
+/// Will return `Err` if the answer or the parent comment does not exist, the profanity
+/// check fails or the database query fails.
+pub async fn add_answer_comment(
+    answer_id: i32,
+    session: Session,
+    store: Store,
+    new_comment: NewComment,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    add_comment(
+        CommentTarget::Answer(answer_id),
+        session,
+        store,
+        new_comment,
+    )
+    .await
+}
+
+/// # Errors
+///
+/// Will return `Err` if the session doesn't own the comment, the profanity check fails or
+/// the database query fails.
+pub async fn update_comment(
+    id: i32,
+    session: Session,
+    store: Store,
+    comment: NewComment,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    if store.is_comment_owner(id, &account_id).await? {
+        let content = match store.check_profanity(comment.content).await {
+            Ok(res) => res.content,
+            Err(e) => return Err(warp::reject::custom(e)),
+        };
+
+        match store.update_comment(id, content, account_id).await {
+            Ok(res) => Ok(warp::reply::json(&res)),
+            Err(e) => Err(warp::reject::custom(e)),
+        }
+    } else {
+        Err(warp::reject::custom(handle_errors::Error::Unauthorized))
+    }
+}
+
+/// # Errors
+///
+/// Will return `Err` if the session doesn't own the comment or the database query fails.
+pub async fn delete_comment(
+    id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    if store.is_comment_owner(id, &account_id).await? {
+        match store.delete_comment(id, &account_id).await {
+            Ok(_) => Ok(warp::reply::with_status(
+                format!("Comment {id} deleted"),
+                StatusCode::OK,
+            )),
+            Err(e) => Err(warp::reject::custom(e)),
+        }
+    } else {
+        Err(warp::reject::custom(handle_errors::Error::Unauthorized))
+    }
+}
+
+#[instrument]
+async fn get_comments(
+    target: CommentTarget,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    event!(target: "eroteme", Level::INFO, "querying comments on {target:?}");
+
+    if !store.comment_target_exists(target).await? {
+        return Err(warp::reject::custom(handle_errors::Error::NotFound));
+    }
+
+    match store.get_comments(target).await {
+        Ok(res) => Ok(warp::reply::json(&res)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+async fn add_comment(
+    target: CommentTarget,
+    session: Session,
+    store: Store,
+    new_comment: NewComment,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+
+    if !store.comment_target_exists(target).await? {
+        return Err(warp::reject::custom(handle_errors::Error::NotFound));
+    }
+
+    // Replies have to stay on the same question or answer as their parent
+    if let Some(parent_id) = &new_comment.parent_id {
+        let parent = store.clone().get_comment(parent_id.0).await?;
+        if CommentTarget::of(&parent) != Some(target) {
+            return Err(warp::reject::custom(
+                handle_errors::Error::InvalidParameter(
+                    "parent comment belongs to a different post".to_owned(),
+                ),
+            ));
+        }
+    }
+
+    let content = match store.check_profanity(new_comment.content).await {
+        Ok(res) => res.content,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+
+    let comment = NewComment {
+        content,
+        parent_id: new_comment.parent_id,
+    };
+
+    match store.add_comment(target, comment, account_id).await {
+        Ok(comment) => Ok(warp::reply::with_status(
+            warp::reply::json(&comment),
+            StatusCode::CREATED,
+        )),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
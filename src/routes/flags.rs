@@ -0,0 +1,74 @@
+use crate::store::Store;
+use crate::types::pagination::{self, Pagination};
+use crate::types::{ModerationTarget, NewFlag, Session};
+use std::collections::HashMap;
+use tracing::{event, instrument, Level};
+use warp::http::StatusCode;
+
+/// # Errors
+///
+/// Will return `Err` if the question does not exist or the database query fails.
+pub async fn flag_question(
+    id: i32,
+    session: Session,
+    store: Store,
+    new_flag: NewFlag,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    flag(ModerationTarget::Question(id), session, store, new_flag).await
+}
+
+/// # Errors
+///
+/// Will return `Err` if the answer does not exist or the database query fails.
+pub async fn flag_answer(
+    id: i32,
+    session: Session,
+    store: Store,
+    new_flag: NewFlag,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    flag(ModerationTarget::Answer(id), session, store, new_flag).await
+}
+
+/// # Errors
+///
+/// Will return `Err` if the pagination parameters are invalid or the database query fails.
+#[instrument]
+pub async fn get_flagged_posts(
+    params: HashMap<String, String>,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    event!(target: "eroteme", Level::INFO, "querying flagged posts");
+
+    let pagination = if params.is_empty() {
+        Pagination::default()
+    } else {
+        pagination::extract_pagination(&params)?
+    };
+
+    match store
+        .get_flagged_posts(pagination.limit, pagination.offset)
+        .await
+    {
+        Ok(res) => Ok(warp::reply::json(&res)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+async fn flag(
+    target: ModerationTarget,
+    session: Session,
+    store: Store,
+    new_flag: NewFlag,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match store
+        .flag(target, &session.account_id, new_flag.reason)
+        .await
+    {
+        Ok(flag) => Ok(warp::reply::with_status(
+            warp::reply::json(&flag),
+            StatusCode::CREATED,
+        )),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
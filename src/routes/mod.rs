@@ -1,8 +1,47 @@
+pub mod account;
+pub use account::{change_password, delete_account};
+
+pub mod admin;
+pub use admin::{get_audit_log, get_metrics, set_role};
+
 pub mod answers;
-pub use answers::add_answer;
+pub use answers::{add_answer, delete_answer, get_answers, update_answer};
 
 pub mod authentication;
-pub use authentication::{auth, login, register};
+pub use authentication::{
+    auth, client_ip, login, logout, logout_everywhere, refresh, register, require_role,
+    require_verified, resend_verification, verify_email,
+};
+
+pub mod comments;
+pub use comments::{
+    add_answer_comment, add_question_comment, delete_comment, get_answer_comments,
+    get_question_comments, update_comment,
+};
+
+pub mod flags;
+pub use flags::{flag_answer, flag_question, get_flagged_posts};
+
+pub mod moderation;
+pub use moderation::{
+    approve_answer, approve_question, get_moderation_queue, hide_answer, hide_question,
+    remove_answer, remove_question,
+};
+
+pub mod password;
+pub use password::{forgot_password, reset_password};
 
 pub mod questions;
-pub use questions::{add_question, delete_question, get_questions, update_question};
+pub use questions::{
+    accept_answer, add_question, delete_question, get_question, get_questions, unaccept_answer,
+    update_question,
+};
+
+pub mod search;
+pub use search::search;
+
+pub mod tags;
+pub use tags::get_tags;
+
+pub mod votes;
+pub use votes::{retract_answer_vote, retract_question_vote, vote_answer, vote_question};
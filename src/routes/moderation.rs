@@ -0,0 +1,176 @@
+use crate::store::Store;
+use crate::types::pagination::{self, Pagination};
+use crate::types::{ModerationStatus, ModerationTarget, Session};
+use std::collections::HashMap;
+use tracing::{event, instrument, Level};
+use warp::http::StatusCode;
+
+/// # Errors
+///
+/// Will return `Err` if the pagination parameters are invalid or the database query fails.
+#[instrument]
+pub async fn get_moderation_queue(
+    params: HashMap<String, String>,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    event!(target: "eroteme", Level::INFO, "querying moderation queue");
+
+    let pagination = if params.is_empty() {
+        Pagination::default()
+    } else {
+        pagination::extract_pagination(&params)?
+    };
+
+    match store
+        .get_moderation_queue(pagination.limit, pagination.offset)
+        .await
+    {
+        Ok(res) => Ok(warp::reply::json(&res)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+/// # Errors
+///
+/// Will return `Err` if the question does not exist or the database query fails.
+pub async fn approve_question(
+    id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    set_status(
+        ModerationTarget::Question(id),
+        ModerationStatus::Approved,
+        session,
+        store,
+    )
+    .await
+}
+
+/// # Errors
+///
+/// Will return `Err` if the question does not exist or the database query fails.
+pub async fn hide_question(
+    id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    set_status(
+        ModerationTarget::Question(id),
+        ModerationStatus::Hidden,
+        session,
+        store,
+    )
+    .await
+}
+
+/// # Errors
+///
+/// Will return `Err` if the question does not exist or the database query fails.
+pub async fn remove_question(
+    id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    remove(ModerationTarget::Question(id), session, store).await
+}
+
+/// # Errors
+///
+/// Will return `Err` if the answer does not exist or the database query fails.
+pub async fn approve_answer(
+    id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    set_status(
+        ModerationTarget::Answer(id),
+        ModerationStatus::Approved,
+        session,
+        store,
+    )
+    .await
+}
+
+/// # Errors
+///
+/// Will return `Err` if the answer does not exist or the database query fails.
+pub async fn hide_answer(
+    id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    set_status(
+        ModerationTarget::Answer(id),
+        ModerationStatus::Hidden,
+        session,
+        store,
+    )
+    .await
+}
+
+/// # Errors
+///
+/// Will return `Err` if the answer does not exist or the database query fails.
+pub async fn remove_answer(
+    id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    remove(ModerationTarget::Answer(id), session, store).await
+}
+
+async fn set_status(
+    target: ModerationTarget,
+    status: ModerationStatus,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    event!(
+        target: "eroteme",
+        Level::INFO,
+        "setting {target:?} to {status:?} by {:?}",
+        session.account_id
+    );
+
+    match store
+        .set_moderation_status(target, status, &session.account_id)
+        .await
+    {
+        Ok(()) => {
+            let verb = match status {
+                ModerationStatus::Approved => "approved",
+                ModerationStatus::PendingReview => "queued for review",
+                ModerationStatus::Hidden => "hidden",
+                ModerationStatus::Unchecked => "queued for a profanity check",
+            };
+            Ok(warp::reply::with_status(
+                format!("{target} {verb}"),
+                StatusCode::OK,
+            ))
+        }
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+async fn remove(
+    target: ModerationTarget,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    event!(
+        target: "eroteme",
+        Level::INFO,
+        "removing {target:?} by {:?}",
+        session.account_id
+    );
+
+    match store.remove_post(target, &session.account_id).await {
+        Ok(()) => Ok(warp::reply::with_status(
+            format!("{target} deleted"),
+            StatusCode::OK,
+        )),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
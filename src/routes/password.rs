@@ -0,0 +1,205 @@
+use crate::limits;
+use crate::mailer::Email;
+use crate::routes::authentication::{hash_token, random_token, spawn_hash};
+use crate::store::Store;
+use crate::types::{ForgotPassword, PasswordReset};
+use std::net::IpAddr;
+use tracing::{event, Level};
+use warp::http::StatusCode;
+
+/// Reset tokens stay valid for this long
+const RESET_HOURS: i32 = 1;
+
+/// Names the routes are rate limited under, each has its own limits
+const PASSWORD_FORGOT: &str = "password_forgot";
+const PASSWORD_RESET: &str = "password_reset";
+
+/// Email a password reset token if an account uses the address. The reply is the same
+/// whether it does or not, and the work happens after replying so the response time gives
+/// nothing away either.
+///
+/// # Errors
+///
+/// Will return `Err` if the client or email is rate limited.
+pub async fn forgot_password(
+    store: Store,
+    ip: Option<IpAddr>,
+    request: ForgotPassword,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    store
+        .limits
+        .check(PASSWORD_FORGOT, ip, &limits::limit_key(&request.email))?;
+
+    tokio::task::spawn(async move {
+        if let Err(e) = send_reset(&store, request.email.trim()).await {
+            event!(Level::ERROR, "cannot send password reset email: {e}");
+        }
+    });
+
+    Ok(warp::reply::with_status(
+        "if an account uses this email address, a reset token is on its way".to_owned(),
+        StatusCode::ACCEPTED,
+    ))
+}
+
+async fn send_reset(store: &Store, email: &str) -> Result<(), handle_errors::Error> {
+    let (token, hash) = random_token();
+    if !store.add_password_reset(email, &hash, RESET_HOURS).await? {
+        return Ok(());
+    }
+
+    store
+        .mailer
+        .send(Email {
+            to: email.to_owned(),
+            subject: "Reset your password".to_owned(),
+            body: format!(
+                "Someone asked to reset the password of your account. If it was you, send \
+                this token with your new password to {}/password/reset within \
+                {RESET_HOURS} hour:\n\n{token}\n\nOtherwise you can ignore this email.",
+                store.accounts.public_url.trim_end_matches('/')
+            ),
+        })
+        .await
+}
+
+/// Set a new password with a reset token, logging the account out everywhere.
+///
+/// # Errors
+///
+/// Will return `Err` if the client is rate limited, the password doesn't follow the password
+/// policy, the token is unknown, expired or was already used, or the database query fails.
+pub async fn reset_password(
+    store: Store,
+    ip: Option<IpAddr>,
+    reset: PasswordReset,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    store.limits.check_ip(PASSWORD_RESET, ip)?;
+    store.accounts.check_password(&reset.password)?;
+    let password = spawn_hash(reset.password, store.accounts.argon2).await;
+
+    match store
+        .reset_password(&hash_token(&reset.token), &password)
+        .await?
+    {
+        Some(account_id) => {
+            event!(target: "eroteme", Level::INFO, "password reset for {account_id:?}");
+            Ok(warp::reply::with_status(
+                "password changed, log in again".to_owned(),
+                StatusCode::OK,
+            ))
+        }
+        None => Err(warp::reject::custom(
+            handle_errors::Error::InvalidResetToken,
+        )),
+    }
+}
+
+#[cfg(test)]
+mod password_tests {
+    use super::{forgot_password, reset_password};
+    use crate::limits::{AuthLimits, Lockout, LockoutPolicy, RateLimitPolicy, RateLimiter};
+    use crate::store::Store;
+    use crate::types::{ForgotPassword, PasswordReset};
+    use handle_errors::Error;
+    use std::net::{IpAddr, Ipv4Addr};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use warp::http::StatusCode;
+    use warp::Reply;
+
+    const ALICE: Option<IpAddr> = Some(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)));
+    const BOB: Option<IpAddr> = Some(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 2)));
+
+    /// One request per address and email a minute
+    fn store() -> Store {
+        let policy = RateLimitPolicy {
+            max_requests: 1,
+            window: Duration::from_secs(60),
+        };
+        let mut store = Store::lazy();
+        store.limits = Arc::new(AuthLimits {
+            by_ip: RateLimiter::new(policy),
+            by_email: RateLimiter::new(policy),
+            lockout: Lockout::new(LockoutPolicy {
+                threshold: 0,
+                base: Duration::ZERO,
+                max: Duration::ZERO,
+            }),
+            trust_forwarded_for: false,
+        });
+        store
+    }
+
+    fn forgot(email: &str) -> ForgotPassword {
+        ForgotPassword {
+            email: email.to_owned(),
+        }
+    }
+
+    fn reset() -> PasswordReset {
+        PasswordReset {
+            token: "token".to_owned(),
+            password: "short".to_owned(),
+        }
+    }
+
+    #[tokio::test]
+    async fn rate_limit_reset_emails() {
+        let store = store();
+
+        let reply = forgot_password(store.clone(), ALICE, forgot("alice@email.com"))
+            .await
+            .unwrap();
+        assert_eq!(reply.into_response().status(), StatusCode::ACCEPTED);
+
+        // Limited by email, whatever the address and case
+        let Err(rejection) = forgot_password(store.clone(), BOB, forgot(" Alice@Email.com")).await
+        else {
+            panic!("a second reset email was sent");
+        };
+        assert!(matches!(
+            rejection.find::<Error>(),
+            Some(Error::TooManyRequests(_))
+        ));
+
+        // Limited by address, whatever the email
+        let Err(rejection) = forgot_password(store, ALICE, forgot("bob@email.com")).await else {
+            panic!("a second reset email was sent");
+        };
+        assert!(matches!(
+            rejection.find::<Error>(),
+            Some(Error::TooManyRequests(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn rate_limit_resets_by_address() {
+        let store = store();
+
+        // Counted before the password is checked, so refused attempts use up the limit
+        let Err(rejection) = reset_password(store.clone(), ALICE, reset()).await else {
+            panic!("a weak password was accepted");
+        };
+        assert!(matches!(
+            rejection.find::<Error>(),
+            Some(Error::WeakPassword(_))
+        ));
+
+        let Err(rejection) = reset_password(store.clone(), ALICE, reset()).await else {
+            panic!("a weak password was accepted");
+        };
+        assert!(matches!(
+            rejection.find::<Error>(),
+            Some(Error::TooManyRequests(_))
+        ));
+
+        let Err(rejection) = reset_password(store, BOB, reset()).await else {
+            panic!("a weak password was accepted");
+        };
+        assert!(matches!(
+            rejection.find::<Error>(),
+            Some(Error::WeakPassword(_))
+        ));
+    }
+}
@@ -1,34 +1,46 @@
-use crate::types::pagination::{self, Pagination};
-use crate::types::{NewQuestion, Question, Session};
-use crate::{check_profanity, Store};
-use std::collections::HashMap;
+use crate::types::{query, tag};
+use crate::types::{AnswerSort, NewQuestion, QuestionWithAnswers, Session};
+use crate::Store;
 use tracing::{event, instrument, Level};
 use warp::http::StatusCode;
 
-// TODO: get a single question with an id param
+/// # Errors
+///
+/// Will return `Err` if the query parameters are invalid or the database query fails.
 #[instrument]
 pub async fn get_questions(
-    params: HashMap<String, String>,
+    params: Vec<(String, String)>,
     store: Store,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     event!(target: "eroteme", Level::INFO, "querying questions");
 
-    let pagination = if params.is_empty() {
-        Pagination::default()
-    } else {
-        event!(Level::INFO, pagination = true);
-        pagination::extract_pagination(&params)?
-    };
+    let query = query::extract_question_query(&params)?;
+    event!(Level::INFO, query = ?query);
 
-    match store
-        .get_questions(pagination.limit, pagination.offset)
-        .await
-    {
+    match store.get_questions(query).await {
         Ok(res) => Ok(warp::reply::json(&res)),
         Err(e) => Err(warp::reject::custom(e)),
     }
 }
 
+/// # Errors
+///
+/// Will return `Err` if the question does not exist or the database query fails.
+#[instrument]
+pub async fn get_question(id: i32, store: Store) -> Result<impl warp::Reply, warp::Rejection> {
+    event!(target: "eroteme", Level::INFO, "querying question {id}");
+
+    let question = store.clone().get_question(id).await?;
+    let answers = store
+        .get_answers(id, AnswerSort::default(), None, 0)
+        .await?;
+
+    Ok(warp::reply::json(&QuestionWithAnswers {
+        question,
+        answers,
+    }))
+}
+
 /// # Errors
 ///
 /// Will return `Err` if the Warp filter fails to match the route
@@ -36,31 +48,28 @@ pub async fn update_question(
     id: i32,
     session: Session,
     store: Store,
-    question: Question,
+    question: NewQuestion,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     let account_id = session.account_id;
     if store.is_question_owner(id, &account_id).await? {
-        let title = check_profanity(question.title);
-        let content = check_profanity(question.content);
-
-        let (title, content) = tokio::join!(title, content);
-
-        if title.is_ok() && content.is_ok() {
-            let question = Question {
-                id: question.id,
-                title: title?,
-                content: content?,
-                tags: question.tags,
-            };
-
-            match store.update_question(question, id, account_id).await {
-                Ok(res) => Ok(warp::reply::json(&res)),
-                Err(e) => Err(warp::reject::custom(e)),
-            }
-        } else {
-            Err(warp::reject::custom(
-                title.expect_err("expected api call to have failed here"),
-            ))
+        let tags = tag::normalize_tags(question.tags)?;
+        let (title, content) = store
+            .moderate_question(question.title, question.content)
+            .await?;
+
+        let moderation_status = title.status.max(content.status);
+        let question = NewQuestion {
+            title: title.content,
+            content: content.content,
+            tags,
+        };
+
+        match store
+            .update_question(question, moderation_status, id, account_id)
+            .await
+        {
+            Ok(res) => Ok(warp::reply::json(&res)),
+            Err(e) => Err(warp::reject::custom(e)),
         }
     } else {
         Err(warp::reject::custom(handle_errors::Error::Unauthorized))
@@ -98,31 +107,68 @@ pub async fn add_question(
     new_question: NewQuestion,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     let account_id = session.account_id;
-    let title = check_profanity(new_question.title);
-    let content = check_profanity(new_question.content);
-
-    let (title, content) = tokio::join!(title, content);
-
-    if title.is_err() {
-        return Err(warp::reject::custom(
-            title.expect_err("profanity check on title failed"),
-        ));
-    }
-
-    if content.is_err() {
-        return Err(warp::reject::custom(
-            content.expect_err("profanity check on content failed"),
-        ));
-    }
+    let tags = tag::normalize_tags(new_question.tags)?;
+    let (title, content) = store
+        .moderate_question(new_question.title, new_question.content)
+        .await?;
 
+    let moderation_status = title.status.max(content.status);
     let question = NewQuestion {
-        title: title?,
-        content: content?,
-        tags: new_question.tags,
+        title: title.content,
+        content: content.content,
+        tags,
     };
 
-    match store.add_questions(question, account_id).await {
+    match store
+        .add_questions(question, moderation_status, account_id)
+        .await
+    {
         Ok(question) => Ok(warp::reply::json(&question)),
         Err(e) => Err(warp::reject::custom(e)),
     }
 }
+
+/// # Errors
+///
+/// Will return `Err` if the session doesn't own the question, the answer doesn't belong to
+/// the question or the database query fails.
+pub async fn accept_answer(
+    id: i32,
+    answer_id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    if store.is_question_owner(id, &account_id).await? {
+        store.clone().get_answer(answer_id, id).await?;
+
+        match store
+            .set_accepted_answer(id, Some(answer_id), &account_id)
+            .await
+        {
+            Ok(res) => Ok(warp::reply::json(&res)),
+            Err(e) => Err(warp::reject::custom(e)),
+        }
+    } else {
+        Err(warp::reject::custom(handle_errors::Error::Unauthorized))
+    }
+}
+
+/// # Errors
+///
+/// Will return `Err` if the session doesn't own the question or the database query fails.
+pub async fn unaccept_answer(
+    id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    if store.is_question_owner(id, &account_id).await? {
+        match store.set_accepted_answer(id, None, &account_id).await {
+            Ok(res) => Ok(warp::reply::json(&res)),
+            Err(e) => Err(warp::reject::custom(e)),
+        }
+    } else {
+        Err(warp::reject::custom(handle_errors::Error::Unauthorized))
+    }
+}
@@ -0,0 +1,30 @@
+use crate::store::Store;
+use crate::types::search;
+use std::collections::HashMap;
+use tracing::{event, instrument, Level};
+
+/// # Errors
+///
+/// Will return `Err` if the search terms are missing, the pagination parameters are invalid
+/// or the database query fails.
+#[instrument]
+pub async fn search(
+    params: HashMap<String, String>,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    event!(target: "eroteme", Level::INFO, "searching questions and answers");
+
+    let query = search::extract_search(&params)?;
+
+    match store
+        .search(
+            &query.terms,
+            query.pagination.limit,
+            query.pagination.offset,
+        )
+        .await
+    {
+        Ok(res) => Ok(warp::reply::json(&res)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
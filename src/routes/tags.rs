@@ -0,0 +1,27 @@
+use crate::store::Store;
+use crate::types::pagination::{self, Pagination};
+use std::collections::HashMap;
+use tracing::{event, instrument, Level};
+
+/// # Errors
+///
+/// Will return `Err` if the pagination parameters are invalid or the database query fails.
+#[instrument]
+pub async fn get_tags(
+    params: HashMap<String, String>,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    event!(target: "eroteme", Level::INFO, "querying tags");
+
+    let pagination = if params.is_empty() {
+        Pagination::default()
+    } else {
+        event!(Level::INFO, pagination = true);
+        pagination::extract_pagination(&params)?
+    };
+
+    match store.get_tags(pagination.limit, pagination.offset).await {
+        Ok(res) => Ok(warp::reply::json(&res)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
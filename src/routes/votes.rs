@@ -0,0 +1,88 @@
+use crate::store::Store;
+use crate::types::{Session, Vote};
+
+/// # Errors
+///
+/// Will return `Err` if the question does not exist, belongs to the voter or the database
+/// query fails.
+pub async fn vote_question(
+    id: i32,
+    session: Session,
+    store: Store,
+    vote: Vote,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    store.clone().get_question(id).await?;
+
+    if store.is_question_owner(id, &account_id).await? {
+        return Err(warp::reject::custom(handle_errors::Error::SelfVote));
+    }
+
+    match store.vote_question(id, &account_id, vote.value).await {
+        Ok(score) => Ok(warp::reply::json(&score)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+/// # Errors
+///
+/// Will return `Err` if the question does not exist or the database query fails.
+pub async fn retract_question_vote(
+    id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    store.clone().get_question(id).await?;
+
+    match store.retract_question_vote(id, &session.account_id).await {
+        Ok(score) => Ok(warp::reply::json(&score)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+/// # Errors
+///
+/// Will return `Err` if the answer does not exist, belongs to the voter or the database
+/// query fails.
+pub async fn vote_answer(
+    question_id: i32,
+    answer_id: i32,
+    session: Session,
+    store: Store,
+    vote: Vote,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    store.clone().get_answer(answer_id, question_id).await?;
+
+    if store
+        .is_answer_owner(answer_id, question_id, &account_id)
+        .await?
+    {
+        return Err(warp::reject::custom(handle_errors::Error::SelfVote));
+    }
+
+    match store.vote_answer(answer_id, &account_id, vote.value).await {
+        Ok(score) => Ok(warp::reply::json(&score)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+/// # Errors
+///
+/// Will return `Err` if the answer does not exist or the database query fails.
+pub async fn retract_answer_vote(
+    question_id: i32,
+    answer_id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    store.clone().get_answer(answer_id, question_id).await?;
+
+    match store
+        .retract_answer_vote(answer_id, &session.account_id)
+        .await
+    {
+        Ok(score) => Ok(warp::reply::json(&score)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
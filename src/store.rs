@@ -1,14 +1,58 @@
-use crate::types::{Account, AccountId, Answer, AnswerId};
-use crate::types::{NewAnswer, NewQuestion, Question, QuestionId};
-use crate::Config;
+use crate::config::{MailerBackend, ProfanityBackend};
+use crate::limits::{AuthLimits, Lockout, LockoutPolicy, RateLimitPolicy, RateLimiter};
+use crate::mailer::{LogMailer, Mailer, SmtpMailer};
+use crate::profanity::{ApiLayerFilter, BreakerPolicy, CachedFilter, CircuitBreaker};
+use crate::profanity::{Censored, ProfanityFilter};
+use crate::profanity::{ProfanityMetrics, WordlistFilter};
+use crate::types::moderation::Moderated;
+use crate::types::pagination::{Cursor, Page};
+use crate::types::{
+    Account, AccountId, AccountPolicy, Answer, AnswerId, BreachedPasswords, DeletedContent,
+};
+use crate::types::{AnswerSort, QuestionQuery, QuestionSort, Tag, TagMode};
+use crate::types::{AuditAction, AuditEntry, AuditTarget, NewRole};
+use crate::types::{Comment, CommentId, CommentTarget, NewComment};
+use crate::types::{Flag, FlagReason, FlaggedPost};
+use crate::types::{ModeratedField, ModerationPolicy, ModerationStatus};
+use crate::types::{ModerationQueue, ModerationTarget};
+use crate::types::{NewAnswer, NewQuestion, Question, QuestionId, SearchResult};
+use crate::types::{Role, SessionId};
+use crate::types::{Score, VoteValue};
+use crate::{Config, Keyring};
 use handle_errors::Error;
-use sqlx::postgres::{PgPool, PgPoolOptions, PgRow};
-use sqlx::Row;
+use sqlx::postgres::{PgConnection, PgPool, PgPoolOptions, PgRow, Postgres};
+use sqlx::{QueryBuilder, Row};
+use std::sync::Arc;
+use std::time::Duration;
 use tracing_subscriber::fmt::format::FmtSpan;
 
+/// Posts of deleted accounts are kept under this id, account ids start at 1
+const DELETED_ACCOUNT_ID: i32 = 0;
+
+/// How long a recheck claims a post for. If the instance that claimed it stops before
+/// storing a result, another one picks the post up after this.
+const RECHECK_LEASE_SECS: i32 = 300;
+
+/// Options passed to `ts_headline` when building search snippets
+const SEARCH_HEADLINE_OPTIONS: &str =
+    "StartSel=<mark>, StopSel=</mark>, MaxWords=35, MinWords=15, MaxFragments=2";
+
+/// Question or answer a vote is cast on
+#[derive(Clone, Copy, Debug)]
+enum VoteTarget {
+    Question(i32),
+    Answer(i32),
+}
+
 #[derive(Clone, Debug)]
 pub struct Store {
     pub connection: PgPool,
+    pub profanity_filter: Arc<dyn ProfanityFilter>,
+    pub moderation: ModerationPolicy,
+    pub keyring: Arc<Keyring>,
+    pub mailer: Arc<dyn Mailer>,
+    pub accounts: AccountPolicy,
+    pub limits: Arc<AuthLimits>,
 }
 
 impl Store {
@@ -19,7 +63,15 @@ impl Store {
     /// # Panics
     ///
     /// Will panic if fails to establish a database connection.
-    pub async fn new(db_url: &str) -> Result<Self, sqlx::Error> {
+    pub async fn new(
+        db_url: &str,
+        profanity_filter: Arc<dyn ProfanityFilter>,
+        moderation: ModerationPolicy,
+        keyring: Arc<Keyring>,
+        mailer: Arc<dyn Mailer>,
+        accounts: AccountPolicy,
+        limits: Arc<AuthLimits>,
+    ) -> Result<Self, sqlx::Error> {
         let db_pool = match PgPoolOptions::new()
             .max_connections(5)
             .connect(db_url)
@@ -31,30 +83,350 @@ impl Store {
 
         Ok(Self {
             connection: db_pool,
+            profanity_filter,
+            moderation,
+            keyring,
+            mailer,
+            accounts,
+            limits,
+        })
+    }
+
+    /// Run content through the configured profanity filter.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the profanity filter cannot check the content.
+    pub async fn check_profanity(&self, content: String) -> Result<Censored, Error> {
+        self.profanity_filter.check_profanity(content).await
+    }
+
+    /// Check a field for profanity and apply the moderation policy configured for it.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the policy rejects the content, or the profanity filter cannot
+    /// check it and the policy doesn't queue it for later.
+    pub async fn moderate(
+        &self,
+        field: ModeratedField,
+        content: String,
+    ) -> Result<Moderated, Error> {
+        let censored = self.check_profanity(content.clone()).await;
+        self.moderation.apply(field, content, censored)
+    }
+
+    /// Like `moderate`, for the title and content of a question checked in one batch.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the profanity filter cannot check the question or the policy
+    /// rejects it.
+    pub async fn moderate_question(
+        &self,
+        title: String,
+        content: String,
+    ) -> Result<(Moderated, Moderated), Error> {
+        let (censored_title, censored_content) = match self
+            .profanity_filter
+            .check_profanity_batch(vec![title.clone(), content.clone()])
+            .await
+        {
+            Ok(censored) => {
+                let mut censored = censored.into_iter();
+                let (Some(title), Some(content)) = (censored.next(), censored.next()) else {
+                    return Err(Error::ServerError(handle_errors::APILayerError {
+                        status: 500,
+                        message: "missing profanity check result".to_owned(),
+                    }));
+                };
+                (Ok(title), Ok(content))
+            }
+            Err(Error::ProfanityUnavailable(retry_after)) => (
+                Err(Error::ProfanityUnavailable(retry_after)),
+                Err(Error::ProfanityUnavailable(retry_after)),
+            ),
+            Err(e) => return Err(e),
+        };
+
+        let title = self
+            .moderation
+            .apply(ModeratedField::Title, title, censored_title)?;
+        let content = self
+            .moderation
+            .apply(ModeratedField::Content, content, censored_content)?;
+        Ok((title, content))
+    }
+
+    #[must_use]
+    pub fn profanity_metrics(&self) -> Option<ProfanityMetrics> {
+        self.profanity_filter.metrics()
+    }
+
+    /// # Errors
+    ///
+    /// Will return `Err` if the cursor doesn't belong to the requested sort order or the
+    /// database query fails.
+    pub async fn get_questions(self, query: QuestionQuery) -> Result<Page<Question>, Error> {
+        let mut builder = QueryBuilder::<Postgres>::new(
+            "SELECT * FROM (
+                SELECT q.*, (
+                    SELECT COUNT(*) FROM answers a
+                    WHERE a.corresponding_question = q.id AND a.moderation_status = 'approved'
+                ) AS answer_count
+                FROM questions q
+                WHERE q.moderation_status = 'approved'
+            ) questions
+            WHERE TRUE",
+        );
+
+        if let Some(filter) = query.tags {
+            builder.push(match filter.mode {
+                TagMode::Any => " AND tags && ",
+                TagMode::All => " AND tags @> ",
+            });
+            builder.push_bind(filter.tags);
+        }
+
+        if let Some(author) = query.author {
+            builder.push(" AND account_id = ").push_bind(author.0);
+        }
+
+        if let Some(created_after) = query.created_after {
+            builder.push(" AND created_on >= ").push_bind(created_after);
+        }
+
+        if let Some(created_before) = query.created_before {
+            builder.push(" AND created_on < ").push_bind(created_before);
+        }
+
+        match query.accepted {
+            Some(true) => {
+                builder.push(" AND accepted_answer_id IS NOT NULL");
+            }
+            Some(false) => {
+                builder.push(" AND accepted_answer_id IS NULL");
+            }
+            None => {}
+        }
+
+        if query.sort == QuestionSort::Unanswered {
+            builder.push(" AND answer_count = 0");
+        }
+
+        if let Some(cursor) = query.page.after {
+            match (query.sort, cursor.key) {
+                (QuestionSort::MostAnswered, Some(key)) => {
+                    builder.push(" AND (answer_count, created_on, id) < (");
+                    builder.push_bind(key).push(", ");
+                }
+                (QuestionSort::Score, Some(key)) => {
+                    let score = i32::try_from(key).map_err(|_| {
+                        Error::InvalidParameter("cursor score is out of range".to_owned())
+                    })?;
+                    builder.push(" AND (score, created_on, id) < (");
+                    builder.push_bind(score).push(", ");
+                }
+                (QuestionSort::Oldest, None) => {
+                    builder.push(" AND (created_on, id) > (");
+                }
+                (QuestionSort::Newest | QuestionSort::Unanswered, None) => {
+                    builder.push(" AND (created_on, id) < (");
+                }
+                _ => {
+                    return Err(Error::InvalidParameter(
+                        "cursor does not match the sort order".to_owned(),
+                    ))
+                }
+            }
+            builder.push_bind(cursor.created_on).push(", ");
+            builder.push_bind(cursor.id).push(")");
+        }
+
+        builder.push(match query.sort {
+            QuestionSort::Newest | QuestionSort::Unanswered => " ORDER BY created_on DESC, id DESC",
+            QuestionSort::Oldest => " ORDER BY created_on ASC, id ASC",
+            QuestionSort::MostAnswered => " ORDER BY answer_count DESC, created_on DESC, id DESC",
+            QuestionSort::Score => " ORDER BY score DESC, created_on DESC, id DESC",
+        });
+
+        // Ask for one extra row so we know whether there is another page
+        builder.push(" LIMIT ").push_bind(query.page.limit + 1);
+        builder.push(" OFFSET ").push_bind(query.page.offset);
+
+        let sort = query.sort;
+        match builder
+            .build()
+            .map(|row: PgRow| {
+                let question = question_from_row(&row);
+                let cursor = Cursor {
+                    key: match sort {
+                        QuestionSort::MostAnswered => Some(row.get("answer_count")),
+                        QuestionSort::Score => Some(i64::from(question.score)),
+                        _ => None,
+                    },
+                    created_on: question.created_on,
+                    id: question.id.0,
+                };
+                (question, cursor)
+            })
+            .fetch_all(&self.connection)
+            .await
+        {
+            Ok(rows) => {
+                let page = Page::new(rows, query.page.limit, |(_, cursor)| cursor.clone());
+                Ok(Page {
+                    items: page
+                        .items
+                        .into_iter()
+                        .map(|(question, _)| question)
+                        .collect(),
+                    next_cursor: page.next_cursor,
+                    has_more: page.has_more,
+                })
+            }
+            Err(e) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    /// # Errors
+    ///
+    /// Will return `Err` if the database query fails.
+    pub async fn get_tags(self, limit: Option<i32>, offset: i32) -> Result<Vec<Tag>, Error> {
+        match sqlx::query(
+            "SELECT tag, COUNT(*) AS count
+            FROM questions, unnest(tags) AS tag
+            WHERE moderation_status = 'approved'
+            GROUP BY tag
+            ORDER BY count DESC, tag
+            LIMIT $1 OFFSET $2",
+        )
+        .bind(limit)
+        .bind(offset)
+        .map(|row: PgRow| Tag {
+            name: row.get("tag"),
+            count: row.get("count"),
         })
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(tags) => Ok(tags),
+            Err(e) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    /// # Errors
+    ///
+    /// Will return `Err` if the question does not exist or the database query fails.
+    pub async fn get_question(self, question_id: i32) -> Result<Question, Error> {
+        match sqlx::query(
+            "SELECT * from questions WHERE id = $1 AND moderation_status = 'approved'",
+        )
+        .bind(question_id)
+        .map(|row: PgRow| question_from_row(&row))
+        .fetch_optional(&self.connection)
+        .await
+        {
+            Ok(Some(question)) => Ok(question),
+            Ok(None) => Err(Error::NotFound),
+            Err(e) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
     }
 
     /// # Errors
     ///
     /// Will return `Err` if the database query fails.
-    pub async fn get_questions(
+    pub async fn get_answers(
         self,
+        question_id: i32,
+        sort: AnswerSort,
         limit: Option<i32>,
         offset: i32,
-    ) -> Result<Vec<Question>, Error> {
-        match sqlx::query("SELECT * from questions LIMIT $1 OFFSET $2")
-            .bind(limit)
-            .bind(offset)
-            .map(|row: PgRow| Question {
-                id: QuestionId(row.get("id")),
-                title: row.get("title"),
-                content: row.get("content"),
-                tags: row.get("tags"),
-            })
-            .fetch_all(&self.connection)
-            .await
+    ) -> Result<Vec<Answer>, Error> {
+        let order_by = match sort {
+            AnswerSort::Oldest => "created_on, id",
+            AnswerSort::Score => "score DESC, created_on, id",
+        };
+
+        match sqlx::query(&format!(
+            "SELECT id, content, corresponding_question, account_id, created_on, score, moderation_status
+            FROM answers
+            WHERE corresponding_question = $1 AND moderation_status = 'approved'
+            ORDER BY {order_by}
+            LIMIT $2 OFFSET $3"
+        ))
+        .bind(question_id)
+        .bind(limit)
+        .bind(offset)
+        .map(|row: PgRow| answer_from_row(&row))
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(answers) => Ok(answers),
+            Err(e) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    /// Full-text search over question titles, question content and answer content, ranked
+    /// by relevance.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the database query fails.
+    pub async fn search(
+        self,
+        terms: &str,
+        limit: Option<i32>,
+        offset: i32,
+    ) -> Result<Vec<SearchResult>, Error> {
+        match sqlx::query(
+            "WITH query AS (SELECT websearch_to_tsquery('english', $1) AS tsq)
+            SELECT * FROM (
+                SELECT q.id AS question_id, NULL::integer AS answer_id, q.title,
+                    ts_headline('english', q.content, query.tsq, $4) AS snippet,
+                    ts_rank(q.search, query.tsq) AS rank
+                FROM questions q, query
+                WHERE q.search @@ query.tsq AND q.moderation_status = 'approved'
+                UNION ALL
+                SELECT q.id AS question_id, a.id AS answer_id, q.title,
+                    ts_headline('english', a.content, query.tsq, $4) AS snippet,
+                    ts_rank(a.search, query.tsq) AS rank
+                FROM answers a
+                JOIN questions q ON q.id = a.corresponding_question, query
+                WHERE a.search @@ query.tsq
+                    AND a.moderation_status = 'approved'
+                    AND q.moderation_status = 'approved'
+            ) hits
+            ORDER BY rank DESC, question_id, answer_id NULLS FIRST
+            LIMIT $2 OFFSET $3",
+        )
+        .bind(terms)
+        .bind(limit)
+        .bind(offset)
+        .bind(SEARCH_HEADLINE_OPTIONS)
+        .map(|row: PgRow| SearchResult {
+            question_id: QuestionId(row.get("question_id")),
+            answer_id: row.get::<Option<i32>, _>("answer_id").map(AnswerId),
+            title: row.get("title"),
+            snippet: row.get("snippet"),
+            rank: row.get("rank"),
+        })
+        .fetch_all(&self.connection)
+        .await
         {
-            Ok(questions) => Ok(questions),
+            Ok(results) => Ok(results),
             Err(e) => {
                 tracing::event!(tracing::Level::ERROR, "{:?}", e);
                 Err(Error::DatabaseQueryError(e))
@@ -90,23 +462,21 @@ impl Store {
     pub async fn add_questions(
         self,
         new_question: NewQuestion,
+        moderation_status: ModerationStatus,
         account_id: AccountId,
     ) -> Result<Question, Error> {
         match sqlx::query(
-            "INSERT INTO questions (title, Content, tags, account_id) 
-            VALUES ($1, $2, $3, $4) 
-            RETURNING id, title, content, tags",
+            "INSERT INTO questions (title, Content, tags, account_id, moderation_status) 
+            VALUES ($1, $2, $3, $4, $5) 
+            RETURNING id, title, content, tags, created_on, score, accepted_answer_id,
+                moderation_status",
         )
         .bind(new_question.title)
         .bind(new_question.content)
         .bind(new_question.tags)
         .bind(account_id.0)
-        .map(|row: PgRow| Question {
-            id: QuestionId(row.get("id")),
-            title: row.get("title"),
-            content: row.get("content"),
-            tags: row.get("tags"),
-        })
+        .bind(moderation_status)
+        .map(|row: PgRow| question_from_row(&row))
         .fetch_one(&self.connection)
         .await
         {
@@ -118,32 +488,68 @@ impl Store {
         }
     }
 
+    /// Edit a question. A hidden question stays hidden whatever the new profanity check
+    /// says, only a moderator can bring it back.
+    ///
     /// # Errors
     ///
     /// Will return `Err` if the database query fails.
     pub async fn update_question(
         self,
-        question: Question,
+        question: NewQuestion,
+        moderation_status: ModerationStatus,
         id: i32,
         account_id: AccountId,
     ) -> Result<Question, Error> {
         match sqlx::query(
             "UPDATE questions
-            SET title = $1, content = $2, tags = $3
-            WHERE id = $4 AND account_id = $5
-            RETURNING id, title, content, tags",
+            SET title = $1, content = $2, tags = $3,
+                moderation_status = CASE WHEN moderation_status = 'hidden'
+                    THEN moderation_status ELSE $4 END
+            WHERE id = $5 AND account_id = $6
+            RETURNING id, title, content, tags, created_on, score, accepted_answer_id,
+                moderation_status",
         )
         .bind(question.title)
         .bind(question.content)
         .bind(question.tags)
+        .bind(moderation_status)
         .bind(id)
         .bind(account_id.0)
-        .map(|row: PgRow| Question {
-            id: QuestionId(row.get("id")),
-            title: row.get("title"),
-            content: row.get("content"),
-            tags: row.get("tags"),
-        })
+        .map(|row: PgRow| question_from_row(&row))
+        .fetch_one(&self.connection)
+        .await
+        {
+            Ok(question) => Ok(question),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// Mark an answer as the accepted answer of a question, or clear it with `None`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the database query fails.
+    pub async fn set_accepted_answer(
+        self,
+        question_id: i32,
+        answer_id: Option<i32>,
+        account_id: &AccountId,
+    ) -> Result<Question, Error> {
+        match sqlx::query(
+            "UPDATE questions
+            SET accepted_answer_id = $1
+            WHERE id = $2 AND account_id = $3
+            RETURNING id, title, content, tags, created_on, score, accepted_answer_id,
+                moderation_status",
+        )
+        .bind(answer_id)
+        .bind(question_id)
+        .bind(account_id.0)
+        .map(|row: PgRow| question_from_row(&row))
         .fetch_one(&self.connection)
         .await
         {
@@ -180,25 +586,78 @@ impl Store {
         }
     }
 
+    /// # Errors
+    ///
+    /// Will return `Err` if the answer does not exist or the database query fails.
+    pub async fn get_answer(self, answer_id: i32, question_id: i32) -> Result<Answer, Error> {
+        match sqlx::query(
+            "SELECT id, content, corresponding_question, account_id, created_on, score, moderation_status
+            FROM answers
+            WHERE id = $1 AND corresponding_question = $2 AND moderation_status = 'approved'",
+        )
+        .bind(answer_id)
+        .bind(question_id)
+        .map(|row: PgRow| answer_from_row(&row))
+        .fetch_optional(&self.connection)
+        .await
+        {
+            Ok(Some(answer)) => Ok(answer),
+            Ok(None) => Err(Error::NotFound),
+            Err(e) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    /// # Errors
+    ///
+    /// Will return `Err` if database query fails.
+    pub async fn is_answer_owner(
+        &self,
+        answer_id: i32,
+        question_id: i32,
+        account_id: &AccountId,
+    ) -> Result<bool, Error> {
+        match sqlx::query(
+            "SELECT * from answers
+            WHERE id = $1 AND corresponding_question = $2 AND account_id = $3",
+        )
+        .bind(answer_id)
+        .bind(question_id)
+        .bind(account_id.0)
+        .fetch_optional(&self.connection)
+        .await
+        {
+            Ok(answer) => Ok(answer.is_some()),
+            Err(e) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
     /// # Errors
     ///
     /// Will return `Err` if the database query fails.
     pub async fn add_answer(
         self,
         new_answer: NewAnswer,
+        moderation_status: ModerationStatus,
+        question_id: i32,
         account_id: AccountId,
     ) -> Result<Answer, Error> {
         match sqlx::query(
-            "INSERT INTO answers (content, corresponding_question, account_id) VALUES ($1, $2, $3)",
+            "INSERT INTO answers (content, corresponding_question, account_id, moderation_status)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id, content, corresponding_question, account_id, created_on, score,
+                moderation_status",
         )
         .bind(new_answer.content)
-        .bind(new_answer.question_id.0)
+        .bind(question_id)
         .bind(account_id.0)
-        .map(|row: PgRow| Answer {
-            id: AnswerId(row.get("id")),
-            content: row.get("content"),
-            question_id: QuestionId(row.get("question_id")),
-        })
+        .bind(moderation_status)
+        .map(|row: PgRow| answer_from_row(&row))
         .fetch_one(&self.connection)
         .await
         {
@@ -210,38 +669,41 @@ impl Store {
         }
     }
 
-    /// # Errors
-    ///
-    /// Will return `Err` if the adding the account to db fails.
+    /// Edit an answer. A hidden answer stays hidden whatever the new profanity check says,
+    /// only a moderator can bring it back.
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// Will panic if the adding the account to db fails.
-    pub async fn add_account(self, account: Account) -> Result<bool, Error> {
-        match sqlx::query("INSERT INTO accounts (email, password) VALUES ($1, $2)")
-            .bind(account.email)
-            .bind(account.password)
-            .execute(&self.connection)
-            .await
+    /// Will return `Err` if the database query fails.
+    pub async fn update_answer(
+        self,
+        answer: NewAnswer,
+        moderation_status: ModerationStatus,
+        answer_id: i32,
+        question_id: i32,
+        account_id: AccountId,
+    ) -> Result<Answer, Error> {
+        match sqlx::query(
+            "UPDATE answers
+            SET content = $1,
+                moderation_status = CASE WHEN moderation_status = 'hidden'
+                    THEN moderation_status ELSE $2 END
+            WHERE id = $3 AND corresponding_question = $4 AND account_id = $5
+            RETURNING id, content, corresponding_question, account_id, created_on, score,
+                moderation_status",
+        )
+        .bind(answer.content)
+        .bind(moderation_status)
+        .bind(answer_id)
+        .bind(question_id)
+        .bind(account_id.0)
+        .map(|row: PgRow| answer_from_row(&row))
+        .fetch_one(&self.connection)
+        .await
         {
-            Ok(_) => Ok(true),
+            Ok(answer) => Ok(answer),
             Err(error) => {
-                tracing::event!(
-                    tracing::Level::ERROR,
-                    code = error
-                        .as_database_error()
-                        .expect("database error")
-                        .code()
-                        .expect("failed to generate error code")
-                        .parse::<i32>()
-                        .expect("failed parse error code"),
-                    db_message = error.as_database_error().expect("database error").message(),
-                    constraint = error
-                        .as_database_error()
-                        .expect("database error")
-                        .constraint()
-                        .expect("failed to get error constraint"),
-                );
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
                 Err(Error::DatabaseQueryError(error))
             }
         }
@@ -250,36 +712,1580 @@ impl Store {
     /// # Errors
     ///
     /// Will return `Err` if the database query fails.
-    pub async fn get_account(self, email: String) -> Result<Account, Error> {
-        match sqlx::query("SELECT * from accounts where email = $1")
-            .bind(email)
-            .map(|row: PgRow| Account {
-                id: Some(AccountId(row.get("id"))),
-                email: row.get("email"),
-                password: row.get("password"),
-            })
-            .fetch_one(&self.connection)
-            .await
+    pub async fn delete_answer(
+        self,
+        answer_id: i32,
+        question_id: i32,
+        account_id: &AccountId,
+    ) -> Result<bool, Error> {
+        match sqlx::query(
+            "DELETE FROM answers
+            WHERE id = $1 AND corresponding_question = $2 AND account_id = $3",
+        )
+        .bind(answer_id)
+        .bind(question_id)
+        .bind(account_id.0)
+        .execute(&self.connection)
+        .await
         {
-            Ok(account) => Ok(account),
+            Ok(_) => Ok(true),
             Err(error) => {
                 tracing::event!(tracing::Level::ERROR, "{:?}", error);
                 Err(Error::DatabaseQueryError(error))
             }
         }
     }
-}
 
-/// # Errors
-///
-/// Will return `Err` if the database migration fails.
-pub async fn setup(config: &Config) -> Result<Store, handle_errors::Error> {
-    let store = Store::new(&format!(
-        "postgres://{}:{}@{}:{}/{}",
-        config.db_user, config.db_password, config.db_host, config.db_port, config.db_name
-    ))
-    .await
-    .map_err(handle_errors::Error::DatabaseQueryError)?;
+    /// Whether the question or answer exists and is visible, comments follow the moderation
+    /// status of their post like `get_question` and `get_answers` do. An answer is only
+    /// visible on an approved question.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the database query fails.
+    pub async fn comment_target_exists(&self, target: CommentTarget) -> Result<bool, Error> {
+        let query = match target {
+            CommentTarget::Question(id) => sqlx::query(
+                "SELECT id from questions WHERE id = $1 AND moderation_status = 'approved'",
+            )
+            .bind(id),
+            CommentTarget::Answer(id) => sqlx::query(
+                "SELECT a.id from answers a
+                JOIN questions q ON q.id = a.corresponding_question
+                WHERE a.id = $1 AND a.moderation_status = 'approved'
+                    AND q.moderation_status = 'approved'",
+            )
+            .bind(id),
+        };
+
+        match query.fetch_optional(&self.connection).await {
+            Ok(row) => Ok(row.is_some()),
+            Err(e) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    /// # Errors
+    ///
+    /// Will return `Err` if the database query fails.
+    pub async fn get_comments(self, target: CommentTarget) -> Result<Vec<Comment>, Error> {
+        let query = match target {
+            CommentTarget::Question(id) => {
+                sqlx::query("SELECT * from comments WHERE question_id = $1 ORDER BY created_on, id")
+                    .bind(id)
+            }
+            CommentTarget::Answer(id) => {
+                sqlx::query("SELECT * from comments WHERE answer_id = $1 ORDER BY created_on, id")
+                    .bind(id)
+            }
+        };
+
+        match query
+            .map(|row: PgRow| comment_from_row(&row))
+            .fetch_all(&self.connection)
+            .await
+        {
+            Ok(comments) => Ok(comments),
+            Err(e) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    /// # Errors
+    ///
+    /// Will return `Err` if the comment does not exist or the database query fails.
+    pub async fn get_comment(self, comment_id: i32) -> Result<Comment, Error> {
+        match sqlx::query("SELECT * from comments WHERE id = $1")
+            .bind(comment_id)
+            .map(|row: PgRow| comment_from_row(&row))
+            .fetch_optional(&self.connection)
+            .await
+        {
+            Ok(Some(comment)) => Ok(comment),
+            Ok(None) => Err(Error::NotFound),
+            Err(e) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    /// # Errors
+    ///
+    /// Will return `Err` if database query fails.
+    pub async fn is_comment_owner(
+        &self,
+        comment_id: i32,
+        account_id: &AccountId,
+    ) -> Result<bool, Error> {
+        match sqlx::query("SELECT * from comments WHERE id = $1 AND account_id = $2")
+            .bind(comment_id)
+            .bind(account_id.0)
+            .fetch_optional(&self.connection)
+            .await
+        {
+            Ok(comment) => Ok(comment.is_some()),
+            Err(e) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    /// # Errors
+    ///
+    /// Will return `Err` if the database query fails.
+    pub async fn add_comment(
+        self,
+        target: CommentTarget,
+        new_comment: NewComment,
+        account_id: AccountId,
+    ) -> Result<Comment, Error> {
+        let (question_id, answer_id) = match target {
+            CommentTarget::Question(id) => (Some(id), None),
+            CommentTarget::Answer(id) => (None, Some(id)),
+        };
+
+        match sqlx::query(
+            "INSERT INTO comments (content, account_id, question_id, answer_id, parent_id)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING *",
+        )
+        .bind(new_comment.content)
+        .bind(account_id.0)
+        .bind(question_id)
+        .bind(answer_id)
+        .bind(new_comment.parent_id.map(|id| id.0))
+        .map(|row: PgRow| comment_from_row(&row))
+        .fetch_one(&self.connection)
+        .await
+        {
+            Ok(comment) => Ok(comment),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// # Errors
+    ///
+    /// Will return `Err` if the database query fails.
+    pub async fn update_comment(
+        self,
+        comment_id: i32,
+        content: String,
+        account_id: AccountId,
+    ) -> Result<Comment, Error> {
+        match sqlx::query(
+            "UPDATE comments
+            SET content = $1
+            WHERE id = $2 AND account_id = $3
+            RETURNING *",
+        )
+        .bind(content)
+        .bind(comment_id)
+        .bind(account_id.0)
+        .map(|row: PgRow| comment_from_row(&row))
+        .fetch_one(&self.connection)
+        .await
+        {
+            Ok(comment) => Ok(comment),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// # Errors
+    ///
+    /// Will return `Err` if the database query fails.
+    pub async fn delete_comment(
+        self,
+        comment_id: i32,
+        account_id: &AccountId,
+    ) -> Result<bool, Error> {
+        match sqlx::query("DELETE FROM comments WHERE id = $1 AND account_id = $2")
+            .bind(comment_id)
+            .bind(account_id.0)
+            .execute(&self.connection)
+            .await
+        {
+            Ok(_) => Ok(true),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// Cast or change the vote of an account on a question.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the database query fails.
+    pub async fn vote_question(
+        self,
+        question_id: i32,
+        account_id: &AccountId,
+        value: VoteValue,
+    ) -> Result<Score, Error> {
+        self.cast_vote(VoteTarget::Question(question_id), account_id, Some(value))
+            .await
+    }
+
+    /// Retract the vote of an account on a question, if any.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the database query fails.
+    pub async fn retract_question_vote(
+        self,
+        question_id: i32,
+        account_id: &AccountId,
+    ) -> Result<Score, Error> {
+        self.cast_vote(VoteTarget::Question(question_id), account_id, None)
+            .await
+    }
+
+    /// Cast or change the vote of an account on an answer.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the database query fails.
+    pub async fn vote_answer(
+        self,
+        answer_id: i32,
+        account_id: &AccountId,
+        value: VoteValue,
+    ) -> Result<Score, Error> {
+        self.cast_vote(VoteTarget::Answer(answer_id), account_id, Some(value))
+            .await
+    }
+
+    /// Retract the vote of an account on an answer, if any.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the database query fails.
+    pub async fn retract_answer_vote(
+        self,
+        answer_id: i32,
+        account_id: &AccountId,
+    ) -> Result<Score, Error> {
+        self.cast_vote(VoteTarget::Answer(answer_id), account_id, None)
+            .await
+    }
+
+    /// Upsert or delete a vote and recalculate the score of its target in one transaction.
+    /// The target row is locked first so concurrent votes can't compute a stale score.
+    async fn cast_vote(
+        self,
+        target: VoteTarget,
+        account_id: &AccountId,
+        value: Option<VoteValue>,
+    ) -> Result<Score, Error> {
+        let (table, column, id) = match target {
+            VoteTarget::Question(id) => ("questions", "question_id", id),
+            VoteTarget::Answer(id) => ("answers", "answer_id", id),
+        };
+
+        let result: Result<Score, sqlx::Error> = async {
+            let mut tx = self.connection.begin().await?;
+
+            sqlx::query(&format!("SELECT id FROM {table} WHERE id = $1 FOR UPDATE"))
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+
+            match value {
+                Some(value) => {
+                    sqlx::query(&format!(
+                        "INSERT INTO votes (account_id, {column}, value)
+                        VALUES ($1, $2, $3)
+                        ON CONFLICT (account_id, {column}) WHERE {column} IS NOT NULL
+                        DO UPDATE SET value = EXCLUDED.value, created_on = NOW()"
+                    ))
+                    .bind(account_id.0)
+                    .bind(id)
+                    .bind(i16::from(value))
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                None => {
+                    sqlx::query(&format!(
+                        "DELETE FROM votes WHERE account_id = $1 AND {column} = $2"
+                    ))
+                    .bind(account_id.0)
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await?;
+                }
+            }
+
+            let score = sqlx::query(&format!(
+                "UPDATE {table}
+                SET score = (SELECT COALESCE(SUM(value), 0) FROM votes WHERE {column} = $1)
+                WHERE id = $1
+                RETURNING score"
+            ))
+            .bind(id)
+            .map(|row: PgRow| Score {
+                score: row.get("score"),
+            })
+            .fetch_one(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+            Ok(score)
+        }
+        .await;
+
+        match result {
+            Ok(score) => Ok(score),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// Flag a question or answer for moderators. An account has at most one open flag per
+    /// post, flagging again only changes the reason. Posts reaching the flag threshold are
+    /// hidden until a moderator reviews them.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the target does not exist or the database query fails.
+    pub async fn flag(
+        self,
+        target: ModerationTarget,
+        account_id: &AccountId,
+        reason: FlagReason,
+    ) -> Result<Flag, Error> {
+        let table = moderation_table(target);
+        let column = flag_column(target);
+        let flag_threshold = self.moderation.flag_threshold;
+
+        let result: Result<Option<Flag>, sqlx::Error> = async {
+            let mut tx = self.connection.begin().await?;
+
+            // Lock the post so concurrent flags can't both miss the threshold
+            let visible = sqlx::query(&format!(
+                "SELECT id FROM {table} WHERE id = $1 AND moderation_status = 'approved'
+                FOR UPDATE"
+            ))
+            .bind(target.id())
+            .fetch_optional(&mut *tx)
+            .await?;
+            if visible.is_none() {
+                return Ok(None);
+            }
+
+            let flag = sqlx::query(&format!(
+                "INSERT INTO flags (account_id, {column}, reason)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (account_id, {column})
+                    WHERE {column} IS NOT NULL AND resolved_on IS NULL
+                DO UPDATE SET reason = EXCLUDED.reason
+                RETURNING *"
+            ))
+            .bind(account_id.0)
+            .bind(target.id())
+            .bind(reason)
+            .map(|row: PgRow| flag_from_row(&row))
+            .fetch_one(&mut *tx)
+            .await?;
+
+            let hidden = sqlx::query(&format!(
+                "UPDATE {table} SET moderation_status = 'hidden'
+                WHERE id = $1 AND (
+                    SELECT COUNT(*) FROM flags WHERE {column} = $1 AND resolved_on IS NULL
+                ) >= $2"
+            ))
+            .bind(target.id())
+            .bind(flag_threshold)
+            .execute(&mut *tx)
+            .await?;
+            if hidden.rows_affected() > 0 {
+                tracing::event!(tracing::Level::INFO, "{target} hidden after being flagged");
+            }
+
+            tx.commit().await?;
+            Ok(Some(flag))
+        }
+        .await;
+
+        match result {
+            Ok(Some(flag)) => Ok(flag),
+            Ok(None) => Err(Error::NotFound),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// Questions and answers with open flags, most flagged first.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the database query fails.
+    pub async fn get_flagged_posts(
+        self,
+        limit: Option<i32>,
+        offset: i32,
+    ) -> Result<Vec<FlaggedPost>, Error> {
+        match sqlx::query(
+            "SELECT f.question_id, f.answer_id, COUNT(*) AS flags,
+                array_agg(DISTINCT f.reason) AS reasons,
+                COALESCE(q.moderation_status, a.moderation_status) AS moderation_status,
+                MAX(f.created_on) AS last_flagged_on
+            FROM flags f
+            LEFT JOIN questions q ON q.id = f.question_id
+            LEFT JOIN answers a ON a.id = f.answer_id
+            WHERE f.resolved_on IS NULL
+            GROUP BY f.question_id, f.answer_id, q.moderation_status, a.moderation_status
+            ORDER BY flags DESC, last_flagged_on DESC
+            LIMIT $1 OFFSET $2",
+        )
+        .bind(limit)
+        .bind(offset)
+        .map(|row: PgRow| FlaggedPost {
+            question_id: row.get::<Option<i32>, _>("question_id").map(QuestionId),
+            answer_id: row.get::<Option<i32>, _>("answer_id").map(AnswerId),
+            flags: row.get("flags"),
+            reasons: row.get("reasons"),
+            moderation_status: row.get("moderation_status"),
+            last_flagged_on: row.get("last_flagged_on"),
+        })
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(posts) => Ok(posts),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// Questions and answers waiting for review, oldest first. Both are paged together, so
+    /// `limit` counts questions and answers alike and `offset` skips the oldest of either.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the database query fails.
+    pub async fn get_moderation_queue(
+        self,
+        limit: Option<i32>,
+        offset: i32,
+    ) -> Result<ModerationQueue, Error> {
+        let result: Result<ModerationQueue, sqlx::Error> = async {
+            let page: Vec<(bool, i32)> = sqlx::query(
+                "SELECT is_question, id FROM (
+                    SELECT TRUE AS is_question, id, created_on FROM questions
+                    WHERE moderation_status = 'pending_review'
+                    UNION ALL
+                    SELECT FALSE, id, created_on FROM answers
+                    WHERE moderation_status = 'pending_review'
+                ) AS queue
+                ORDER BY created_on, is_question DESC, id
+                LIMIT $1 OFFSET $2",
+            )
+            .bind(limit)
+            .bind(offset)
+            .map(|row: PgRow| (row.get("is_question"), row.get("id")))
+            .fetch_all(&self.connection)
+            .await?;
+            let (question_ids, answer_ids): (Vec<_>, Vec<_>) =
+                page.into_iter().partition(|(is_question, _)| *is_question);
+            let question_ids: Vec<i32> = question_ids.into_iter().map(|(_, id)| id).collect();
+            let answer_ids: Vec<i32> = answer_ids.into_iter().map(|(_, id)| id).collect();
+
+            let questions = sqlx::query(
+                "SELECT * FROM questions
+                WHERE id = ANY($1)
+                ORDER BY created_on, id",
+            )
+            .bind(question_ids)
+            .map(|row: PgRow| question_from_row(&row))
+            .fetch_all(&self.connection)
+            .await?;
+
+            let answers = sqlx::query(
+                "SELECT id, content, corresponding_question, account_id, created_on, score,
+                    moderation_status
+                FROM answers
+                WHERE id = ANY($1)
+                ORDER BY created_on, id",
+            )
+            .bind(answer_ids)
+            .map(|row: PgRow| answer_from_row(&row))
+            .fetch_all(&self.connection)
+            .await?;
+
+            Ok(ModerationQueue { questions, answers })
+        }
+        .await;
+
+        match result {
+            Ok(queue) => Ok(queue),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// Run questions written while the profanity filter was unavailable through the
+    /// moderation policy, oldest first, and store the result.
+    ///
+    /// A batch is claimed for `RECHECK_LEASE_SECS` first, skipping questions another server
+    /// instance has claimed, so each question is sent to the filter once. No transaction or
+    /// lock is held while the filter is called. A result is only stored if the question is
+    /// still unchecked and unchanged, so an edit made in the meantime wins. A question the
+    /// filter fails on for any reason other than an outage goes to a moderator instead, so
+    /// it doesn't hold up the ones behind it.
+    ///
+    /// Returns the number of questions updated, stopping early and releasing the rest of the
+    /// batch if the filter is still unavailable.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the database query fails.
+    pub async fn recheck_questions(&self, limit: i64) -> Result<usize, Error> {
+        let rows: Vec<(i32, String, String)> = sqlx::query(
+            "WITH claimed AS (
+                UPDATE questions SET recheck_claimed_until = NOW() + make_interval(secs => $2)
+                WHERE id IN (
+                    SELECT id FROM questions
+                    WHERE moderation_status = 'unchecked'
+                        AND (recheck_claimed_until IS NULL OR recheck_claimed_until < NOW())
+                    ORDER BY created_on, id
+                    LIMIT $1
+                    FOR UPDATE SKIP LOCKED
+                )
+                RETURNING id, title, content, created_on
+            )
+            SELECT id, title, content FROM claimed ORDER BY created_on, id",
+        )
+        .bind(limit)
+        .bind(RECHECK_LEASE_SECS)
+        .map(|row: PgRow| (row.get("id"), row.get("title"), row.get("content")))
+        .fetch_all(&self.connection)
+        .await
+        .map_err(db_error)?;
+
+        let mut checked = 0;
+        for (i, (id, title, content)) in rows.iter().enumerate() {
+            let (new_title, new_content, status) =
+                match self.moderate_question(title.clone(), content.clone()).await {
+                    Ok((title, content)) => {
+                        let status = title.status.max(content.status);
+                        (title.content, content.content, status)
+                    }
+                    Err(Error::ProfanityUnavailable(_)) => {
+                        self.release_recheck("questions", rows[i..].iter().map(|(id, ..)| *id))
+                            .await?;
+                        break;
+                    }
+                    // The post is already up, so a moderator decides instead of rejecting it
+                    Err(e) => {
+                        recheck_failed(ModerationTarget::Question(*id), &e);
+                        (
+                            title.clone(),
+                            content.clone(),
+                            ModerationStatus::PendingReview,
+                        )
+                    }
+                };
+            if status == ModerationStatus::Unchecked {
+                self.release_recheck("questions", rows[i..].iter().map(|(id, ..)| *id))
+                    .await?;
+                break;
+            }
+
+            let updated = sqlx::query(
+                "UPDATE questions
+                SET title = $1, content = $2, moderation_status = $3, recheck_claimed_until = NULL
+                WHERE id = $4 AND moderation_status = 'unchecked'
+                    AND title = $5 AND content = $6",
+            )
+            .bind(new_title)
+            .bind(new_content)
+            .bind(status)
+            .bind(id)
+            .bind(title)
+            .bind(content)
+            .execute(&self.connection)
+            .await
+            .map_err(db_error)?;
+            if updated.rows_affected() > 0 {
+                checked += 1;
+            }
+        }
+
+        Ok(checked)
+    }
+
+    /// Like `recheck_questions`, for answers.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the database query fails.
+    pub async fn recheck_answers(&self, limit: i64) -> Result<usize, Error> {
+        let rows: Vec<(i32, String)> = sqlx::query(
+            "WITH claimed AS (
+                UPDATE answers SET recheck_claimed_until = NOW() + make_interval(secs => $2)
+                WHERE id IN (
+                    SELECT id FROM answers
+                    WHERE moderation_status = 'unchecked'
+                        AND (recheck_claimed_until IS NULL OR recheck_claimed_until < NOW())
+                    ORDER BY created_on, id
+                    LIMIT $1
+                    FOR UPDATE SKIP LOCKED
+                )
+                RETURNING id, content, created_on
+            )
+            SELECT id, content FROM claimed ORDER BY created_on, id",
+        )
+        .bind(limit)
+        .bind(RECHECK_LEASE_SECS)
+        .map(|row: PgRow| (row.get("id"), row.get("content")))
+        .fetch_all(&self.connection)
+        .await
+        .map_err(db_error)?;
+
+        let mut checked = 0;
+        for (i, (id, content)) in rows.iter().enumerate() {
+            let moderated = match self
+                .moderate(ModeratedField::Content, content.clone())
+                .await
+            {
+                Ok(moderated) => moderated,
+                Err(Error::ProfanityUnavailable(_)) => {
+                    self.release_recheck("answers", rows[i..].iter().map(|(id, _)| *id))
+                        .await?;
+                    break;
+                }
+                Err(e) => {
+                    recheck_failed(ModerationTarget::Answer(*id), &e);
+                    Moderated {
+                        content: content.clone(),
+                        status: ModerationStatus::PendingReview,
+                    }
+                }
+            };
+            if moderated.status == ModerationStatus::Unchecked {
+                self.release_recheck("answers", rows[i..].iter().map(|(id, _)| *id))
+                    .await?;
+                break;
+            }
+
+            let updated = sqlx::query(
+                "UPDATE answers
+                SET content = $1, moderation_status = $2, recheck_claimed_until = NULL
+                WHERE id = $3 AND moderation_status = 'unchecked' AND content = $4",
+            )
+            .bind(moderated.content)
+            .bind(moderated.status)
+            .bind(id)
+            .bind(content)
+            .execute(&self.connection)
+            .await
+            .map_err(db_error)?;
+            if updated.rows_affected() > 0 {
+                checked += 1;
+            }
+        }
+
+        Ok(checked)
+    }
+
+    /// Give up the claim on posts of `table` that weren't rechecked, so the next run can
+    /// take them without waiting for the lease to run out.
+    async fn release_recheck(
+        &self,
+        table: &str,
+        ids: impl Iterator<Item = i32>,
+    ) -> Result<(), Error> {
+        sqlx::query(&format!(
+            "UPDATE {table} SET recheck_claimed_until = NULL WHERE id = ANY($1)"
+        ))
+        .bind(ids.collect::<Vec<_>>())
+        .execute(&self.connection)
+        .await
+        .map(|_| ())
+        .map_err(db_error)
+    }
+
+    /// Approve or hide a question or answer regardless of its owner, and record it in the
+    /// audit log.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the target does not exist or the database query fails.
+    pub async fn set_moderation_status(
+        self,
+        target: ModerationTarget,
+        status: ModerationStatus,
+        moderator_id: &AccountId,
+    ) -> Result<(), Error> {
+        let action = match status {
+            ModerationStatus::Approved => AuditAction::Approve,
+            ModerationStatus::Hidden => AuditAction::Hide,
+            ModerationStatus::PendingReview | ModerationStatus::Unchecked => {
+                return Err(Error::InvalidParameter(
+                    "cannot move a post back to review".to_owned(),
+                ))
+            }
+        };
+        let table = moderation_table(target);
+        let column = flag_column(target);
+
+        let result: Result<bool, sqlx::Error> = async {
+            let mut tx = self.connection.begin().await?;
+
+            let updated = sqlx::query(&format!(
+                "UPDATE {table} SET moderation_status = $1 WHERE id = $2"
+            ))
+            .bind(status)
+            .bind(target.id())
+            .execute(&mut *tx)
+            .await?;
+            if updated.rows_affected() == 0 {
+                return Ok(false);
+            }
+
+            // A moderator looked at the post, so its open flags are dealt with
+            sqlx::query(&format!(
+                "UPDATE flags SET resolved_on = NOW()
+                WHERE {column} = $1 AND resolved_on IS NULL"
+            ))
+            .bind(target.id())
+            .execute(&mut *tx)
+            .await?;
+
+            insert_audit_entry(
+                &mut tx,
+                moderator_id,
+                action,
+                target.into(),
+                target.id(),
+                None,
+            )
+            .await?;
+
+            tx.commit().await?;
+            Ok(true)
+        }
+        .await;
+
+        match result {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(Error::NotFound),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// Delete a question with its answers, or an answer, regardless of the owner, and record
+    /// it in the audit log.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the target does not exist or the database query fails.
+    pub async fn remove_post(
+        self,
+        target: ModerationTarget,
+        moderator_id: &AccountId,
+    ) -> Result<(), Error> {
+        let table = moderation_table(target);
+
+        let result: Result<bool, sqlx::Error> = async {
+            let mut tx = self.connection.begin().await?;
+
+            // Answers don't cascade with their question, comments, votes and flags do
+            if let ModerationTarget::Question(id) = target {
+                sqlx::query("DELETE FROM answers WHERE corresponding_question = $1")
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+
+            let deleted = sqlx::query(&format!("DELETE FROM {table} WHERE id = $1"))
+                .bind(target.id())
+                .execute(&mut *tx)
+                .await?;
+            if deleted.rows_affected() == 0 {
+                return Ok(false);
+            }
+
+            insert_audit_entry(
+                &mut tx,
+                moderator_id,
+                AuditAction::Delete,
+                target.into(),
+                target.id(),
+                None,
+            )
+            .await?;
+
+            tx.commit().await?;
+            Ok(true)
+        }
+        .await;
+
+        match result {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(Error::NotFound),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// Change the role of an account, and record it in the audit log.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the account does not exist or the database query fails.
+    pub async fn set_role(
+        self,
+        account_id: i32,
+        new_role: NewRole,
+        admin_id: &AccountId,
+    ) -> Result<(), Error> {
+        let result: Result<bool, sqlx::Error> = async {
+            let mut tx = self.connection.begin().await?;
+
+            let updated = sqlx::query("UPDATE accounts SET role = $1 WHERE id = $2")
+                .bind(new_role.role)
+                .bind(account_id)
+                .execute(&mut *tx)
+                .await?;
+            if updated.rows_affected() == 0 {
+                return Ok(false);
+            }
+
+            insert_audit_entry(
+                &mut tx,
+                admin_id,
+                AuditAction::SetRole,
+                AuditTarget::Account,
+                account_id,
+                Some(new_role.role.as_str().to_owned()),
+            )
+            .await?;
+
+            tx.commit().await?;
+            Ok(true)
+        }
+        .await;
+
+        match result {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(Error::NotFound),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// Audit log entries, newest first.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the database query fails.
+    pub async fn get_audit_log(
+        self,
+        limit: Option<i32>,
+        offset: i32,
+    ) -> Result<Vec<AuditEntry>, Error> {
+        match sqlx::query(
+            "SELECT * FROM audit_log
+            ORDER BY created_on DESC, id DESC
+            LIMIT $1 OFFSET $2",
+        )
+        .bind(limit)
+        .bind(offset)
+        .map(|row: PgRow| AuditEntry {
+            id: row.get("id"),
+            account_id: AccountId(row.get("account_id")),
+            action: row.get("action"),
+            target: row.get("target"),
+            target_id: row.get("target_id"),
+            details: row.get("details"),
+            created_on: row.get("created_on"),
+        })
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(entries) => Ok(entries),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// # Errors
+    ///
+    /// Will return `Err` if the adding the account to db fails.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the adding the account to db fails.
+    pub async fn add_account(self, account: Account) -> Result<AccountId, Error> {
+        match sqlx::query_scalar(
+            "INSERT INTO accounts (email, password) VALUES ($1, $2) RETURNING id",
+        )
+        .bind(account.email)
+        .bind(account.password)
+        .fetch_one(&self.connection)
+        .await
+        {
+            Ok(id) => Ok(AccountId(id)),
+            Err(error) => {
+                tracing::event!(
+                    tracing::Level::ERROR,
+                    code = error
+                        .as_database_error()
+                        .expect("database error")
+                        .code()
+                        .expect("failed to generate error code")
+                        .parse::<i32>()
+                        .expect("failed parse error code"),
+                    db_message = error.as_database_error().expect("database error").message(),
+                    constraint = error
+                        .as_database_error()
+                        .expect("database error")
+                        .constraint()
+                        .expect("failed to get error constraint"),
+                );
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// The account with an email address, `None` if there is none.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the database query fails.
+    pub async fn get_account(self, email: String) -> Result<Option<Account>, Error> {
+        match sqlx::query("SELECT * from accounts where email = $1")
+            .bind(email)
+            .map(|row: PgRow| account_from_row(&row))
+            .fetch_optional(&self.connection)
+            .await
+        {
+            Ok(account) => Ok(account),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// # Errors
+    ///
+    /// Will return `Err` if the account does not exist or the database query fails.
+    pub async fn get_account_by_id(&self, account_id: &AccountId) -> Result<Account, Error> {
+        sqlx::query("SELECT * from accounts where id = $1")
+            .bind(account_id.0)
+            .map(|row: PgRow| account_from_row(&row))
+            .fetch_optional(&self.connection)
+            .await
+            .map_err(db_error)?
+            .ok_or(Error::NotFound)
+    }
+
+    /// Replace a password hash with one of the same password made with stronger parameters,
+    /// sessions are left alone. Does nothing if the password changed since `old_hash` was
+    /// read.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the database query fails.
+    pub async fn rehash_password(
+        &self,
+        account_id: &AccountId,
+        old_hash: &str,
+        new_hash: &str,
+    ) -> Result<(), Error> {
+        sqlx::query("UPDATE accounts SET password = $1 WHERE id = $2 AND password = $3")
+            .bind(new_hash)
+            .bind(account_id.0)
+            .bind(old_hash)
+            .execute(&self.connection)
+            .await
+            .map(|_| ())
+            .map_err(db_error)
+    }
+
+    /// Set a new password hash and log out every other session of the account.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the database query fails.
+    pub async fn change_password(
+        &self,
+        account_id: &AccountId,
+        password_hash: &str,
+        current_session: SessionId,
+    ) -> Result<(), Error> {
+        let mut tx = self.connection.begin().await.map_err(db_error)?;
+
+        sqlx::query("UPDATE accounts SET password = $1 WHERE id = $2")
+            .bind(password_hash)
+            .bind(account_id.0)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+
+        sqlx::query(
+            "UPDATE sessions SET revoked_on = NOW()
+            WHERE account_id = $1 AND id <> $2 AND revoked_on IS NULL",
+        )
+        .bind(account_id.0)
+        .bind(current_session.0)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+        tx.commit().await.map_err(db_error)
+    }
+
+    /// Delete an account with its sessions, votes and flags, and either anonymize or delete
+    /// its questions, answers and comments, all in one transaction.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the database query fails.
+    pub async fn delete_account(
+        &self,
+        account_id: &AccountId,
+        content: DeletedContent,
+    ) -> Result<(), Error> {
+        let mut tx = self.connection.begin().await.map_err(db_error)?;
+
+        // Take the account's votes out of the scores before deleting them
+        for (table, column) in [("questions", "question_id"), ("answers", "answer_id")] {
+            sqlx::query(&format!(
+                "UPDATE {table} t SET score = t.score - v.value
+                FROM votes v WHERE v.{column} = t.id AND v.account_id = $1"
+            ))
+            .bind(account_id.0)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        }
+
+        for statement in [
+            "DELETE FROM votes WHERE account_id = $1",
+            "DELETE FROM flags WHERE account_id = $1",
+            "DELETE FROM sessions WHERE account_id = $1",
+            "DELETE FROM password_resets WHERE account_id = $1",
+        ] {
+            sqlx::query(statement)
+                .bind(account_id.0)
+                .execute(&mut *tx)
+                .await
+                .map_err(db_error)?;
+        }
+
+        match content {
+            DeletedContent::Anonymize => {
+                for table in ["questions", "answers", "comments"] {
+                    sqlx::query(&format!(
+                        "UPDATE {table} SET account_id = $2 WHERE account_id = $1"
+                    ))
+                    .bind(account_id.0)
+                    .bind(DELETED_ACCOUNT_ID)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(db_error)?;
+                }
+            }
+            // Answers don't cascade with their question, so answers to the account's
+            // questions go first. Comments and votes on deleted posts cascade.
+            DeletedContent::Delete => {
+                for statement in [
+                    "DELETE FROM comments WHERE account_id = $1",
+                    "DELETE FROM answers
+                    WHERE account_id = $1
+                        OR corresponding_question IN (
+                            SELECT id FROM questions WHERE account_id = $1
+                        )",
+                    "DELETE FROM questions WHERE account_id = $1",
+                ] {
+                    sqlx::query(statement)
+                        .bind(account_id.0)
+                        .execute(&mut *tx)
+                        .await
+                        .map_err(db_error)?;
+                }
+            }
+        }
+
+        sqlx::query("DELETE FROM accounts WHERE id = $1")
+            .bind(account_id.0)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+
+        tx.commit().await.map_err(db_error)
+    }
+
+    /// Mark the email address of an account as verified, returns `false` if the account no
+    /// longer exists or its address changed since the link was sent.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the database query fails.
+    pub async fn verify_email(&self, account_id: &AccountId, email: &str) -> Result<bool, Error> {
+        sqlx::query("UPDATE accounts SET verified = TRUE WHERE id = $1 AND email = $2")
+            .bind(account_id.0)
+            .bind(email)
+            .execute(&self.connection)
+            .await
+            .map(|done| done.rows_affected() > 0)
+            .map_err(db_error)
+    }
+
+    /// # Errors
+    ///
+    /// Will return `Err` if the database query fails.
+    pub async fn is_verified(&self, account_id: &AccountId) -> Result<bool, Error> {
+        sqlx::query("SELECT verified FROM accounts WHERE id = $1")
+            .bind(account_id.0)
+            .map(|row: PgRow| row.get("verified"))
+            .fetch_optional(&self.connection)
+            .await
+            .map(|verified| verified.unwrap_or(false))
+            .map_err(db_error)
+    }
+
+    /// Store a password reset token for the account with the given email address, valid for
+    /// `hours`. Returns `false` if there is no such account.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the database query fails.
+    pub async fn add_password_reset(
+        &self,
+        email: &str,
+        token_hash: &[u8],
+        hours: i32,
+    ) -> Result<bool, Error> {
+        sqlx::query(
+            "INSERT INTO password_resets (account_id, token_hash, expires_on)
+            SELECT id, $2, NOW() + make_interval(hours => $3) FROM accounts WHERE email = $1",
+        )
+        .bind(email)
+        .bind(token_hash)
+        .bind(hours)
+        .execute(&self.connection)
+        .await
+        .map(|done| done.rows_affected() > 0)
+        .map_err(db_error)
+    }
+
+    /// Use a password reset token to set a new password hash. Every outstanding reset token
+    /// and session of the account is revoked in the same transaction. Returns `None` if the
+    /// token is unknown, expired or was already used.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the database query fails.
+    pub async fn reset_password(
+        &self,
+        token_hash: &[u8],
+        password_hash: &str,
+    ) -> Result<Option<AccountId>, Error> {
+        let mut tx = self.connection.begin().await.map_err(db_error)?;
+
+        let account_id: Option<i32> = sqlx::query(
+            "UPDATE password_resets SET used_on = NOW()
+            WHERE token_hash = $1 AND used_on IS NULL AND expires_on > NOW()
+            RETURNING account_id",
+        )
+        .bind(token_hash)
+        .map(|row: PgRow| row.get("account_id"))
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(db_error)?;
+        let Some(account_id) = account_id else {
+            return Ok(None);
+        };
+
+        sqlx::query("UPDATE accounts SET password = $1 WHERE id = $2")
+            .bind(password_hash)
+            .bind(account_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+
+        sqlx::query(
+            "UPDATE password_resets SET used_on = NOW()
+            WHERE account_id = $1 AND used_on IS NULL",
+        )
+        .bind(account_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+        sqlx::query(
+            "UPDATE sessions SET revoked_on = NOW()
+            WHERE account_id = $1 AND revoked_on IS NULL",
+        )
+        .bind(account_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+        tx.commit().await.map_err(db_error)?;
+        Ok(Some(AccountId(account_id)))
+    }
+
+    /// Start a session for a login, valid for `days` unless it is refreshed.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the database query fails.
+    pub async fn add_session(
+        &self,
+        account_id: &AccountId,
+        refresh_token_hash: &[u8],
+        days: i32,
+    ) -> Result<SessionId, Error> {
+        sqlx::query(
+            "INSERT INTO sessions (account_id, refresh_token_hash, expires_on)
+            VALUES ($1, $2, NOW() + make_interval(days => $3))
+            RETURNING id",
+        )
+        .bind(account_id.0)
+        .bind(refresh_token_hash)
+        .bind(days)
+        .map(|row: PgRow| SessionId(row.get("id")))
+        .fetch_one(&self.connection)
+        .await
+        .map_err(db_error)
+    }
+
+    /// Swap the refresh token of a live session for a new one and extend it by `days`.
+    /// Returns the session with the current role of its account, or `None` if the old
+    /// token is unknown, expired, revoked or was already used.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the database query fails.
+    pub async fn rotate_session(
+        &self,
+        refresh_token_hash: &[u8],
+        new_refresh_token_hash: &[u8],
+        days: i32,
+    ) -> Result<Option<(SessionId, AccountId, Role)>, Error> {
+        sqlx::query(
+            "UPDATE sessions s
+            SET refresh_token_hash = $2, expires_on = NOW() + make_interval(days => $3)
+            FROM accounts a
+            WHERE s.refresh_token_hash = $1 AND s.revoked_on IS NULL AND s.expires_on > NOW()
+                AND a.id = s.account_id
+            RETURNING s.id, s.account_id, a.role",
+        )
+        .bind(refresh_token_hash)
+        .bind(new_refresh_token_hash)
+        .bind(days)
+        .map(|row: PgRow| {
+            (
+                SessionId(row.get("id")),
+                AccountId(row.get("account_id")),
+                row.get("role"),
+            )
+        })
+        .fetch_optional(&self.connection)
+        .await
+        .map_err(db_error)
+    }
+
+    /// Returns the current role of the account a session belongs to, or `None` if the
+    /// session was revoked or has expired.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the database query fails.
+    pub async fn active_session_role(&self, session_id: SessionId) -> Result<Option<Role>, Error> {
+        sqlx::query(
+            "SELECT a.role FROM sessions s
+            JOIN accounts a ON a.id = s.account_id
+            WHERE s.id = $1 AND s.revoked_on IS NULL AND s.expires_on > NOW()",
+        )
+        .bind(session_id.0)
+        .map(|row: PgRow| row.get("role"))
+        .fetch_optional(&self.connection)
+        .await
+        .map_err(db_error)
+    }
+
+    /// Log out a single session of an account.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the database query fails.
+    pub async fn revoke_session(
+        &self,
+        session_id: SessionId,
+        account_id: &AccountId,
+    ) -> Result<(), Error> {
+        sqlx::query(
+            "UPDATE sessions SET revoked_on = NOW()
+            WHERE id = $1 AND account_id = $2 AND revoked_on IS NULL",
+        )
+        .bind(session_id.0)
+        .bind(account_id.0)
+        .execute(&self.connection)
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+
+    /// Log out every session of an account, returning how many were still live.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the database query fails.
+    pub async fn revoke_sessions(&self, account_id: &AccountId) -> Result<u64, Error> {
+        sqlx::query(
+            "UPDATE sessions SET revoked_on = NOW()
+            WHERE account_id = $1 AND revoked_on IS NULL AND expires_on > NOW()",
+        )
+        .bind(account_id.0)
+        .execute(&self.connection)
+        .await
+        .map(|done| done.rows_affected())
+        .map_err(db_error)
+    }
+}
+
+/// Profane content is expected, anything else is worth a look at the logs
+fn recheck_failed(target: ModerationTarget, e: &Error) {
+    if matches!(e, Error::ProfaneContent(..)) {
+        tracing::event!(tracing::Level::INFO, "{target} needs review: {e}");
+    } else {
+        tracing::event!(tracing::Level::WARN, "cannot recheck {target}: {e}");
+    }
+}
+
+fn db_error(error: sqlx::Error) -> Error {
+    tracing::event!(tracing::Level::ERROR, "{:?}", error);
+    Error::DatabaseQueryError(error)
+}
+
+const fn moderation_table(target: ModerationTarget) -> &'static str {
+    match target {
+        ModerationTarget::Question(_) => "questions",
+        ModerationTarget::Answer(_) => "answers",
+    }
+}
+
+const fn flag_column(target: ModerationTarget) -> &'static str {
+    match target {
+        ModerationTarget::Question(_) => "question_id",
+        ModerationTarget::Answer(_) => "answer_id",
+    }
+}
+
+async fn insert_audit_entry(
+    connection: &mut PgConnection,
+    account_id: &AccountId,
+    action: AuditAction,
+    target: AuditTarget,
+    target_id: i32,
+    details: Option<String>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO audit_log (account_id, action, target, target_id, details)
+        VALUES ($1, $2, $3, $4, $5)",
+    )
+    .bind(account_id.0)
+    .bind(action)
+    .bind(target)
+    .bind(target_id)
+    .bind(details)
+    .execute(connection)
+    .await?;
+    Ok(())
+}
+
+fn question_from_row(row: &PgRow) -> Question {
+    Question {
+        id: QuestionId(row.get("id")),
+        title: row.get("title"),
+        content: row.get("content"),
+        tags: row.get("tags"),
+        created_on: row.get("created_on"),
+        score: row.get("score"),
+        accepted_answer_id: row
+            .get::<Option<i32>, _>("accepted_answer_id")
+            .map(AnswerId),
+        moderation_status: row.get("moderation_status"),
+    }
+}
+
+fn answer_from_row(row: &PgRow) -> Answer {
+    Answer {
+        id: AnswerId(row.get("id")),
+        content: row.get("content"),
+        question_id: QuestionId(row.get("corresponding_question")),
+        account_id: AccountId(row.get("account_id")),
+        created_on: row.get("created_on"),
+        score: row.get("score"),
+        moderation_status: row.get("moderation_status"),
+    }
+}
+
+fn account_from_row(row: &PgRow) -> Account {
+    Account {
+        id: Some(AccountId(row.get("id"))),
+        email: row.get("email"),
+        password: row.get("password"),
+        role: row.get("role"),
+        verified: row.get("verified"),
+    }
+}
+
+fn flag_from_row(row: &PgRow) -> Flag {
+    Flag {
+        id: row.get("id"),
+        account_id: AccountId(row.get("account_id")),
+        question_id: row.get::<Option<i32>, _>("question_id").map(QuestionId),
+        answer_id: row.get::<Option<i32>, _>("answer_id").map(AnswerId),
+        reason: row.get("reason"),
+        created_on: row.get("created_on"),
+    }
+}
+
+fn comment_from_row(row: &PgRow) -> Comment {
+    Comment {
+        id: CommentId(row.get("id")),
+        content: row.get("content"),
+        account_id: AccountId(row.get("account_id")),
+        question_id: row.get::<Option<i32>, _>("question_id").map(QuestionId),
+        answer_id: row.get::<Option<i32>, _>("answer_id").map(AnswerId),
+        parent_id: row.get::<Option<i32>, _>("parent_id").map(CommentId),
+        created_on: row.get("created_on"),
+    }
+}
+
+/// # Errors
+///
+/// Will return `Err` if the database migration fails.
+pub async fn setup(config: &Config) -> Result<Store, handle_errors::Error> {
+    let profanity_filter: Arc<dyn ProfanityFilter> = match config.profanity_filter {
+        // Only the API can go down, the breaker sits below the cache so cached results are
+        // still served while it's open
+        ProfanityBackend::ApiLayer => Arc::new(CircuitBreaker::new(
+            Arc::new(ApiLayerFilter::new(
+                config.bad_words_api_key.clone(),
+                config.api_layer_url.clone(),
+            )),
+            BreakerPolicy {
+                failure_threshold: config.breaker_failure_threshold,
+                open_for: Duration::from_secs(config.breaker_open_secs),
+            },
+        )),
+        ProfanityBackend::Wordlist => {
+            let path = config.wordlist.as_ref().ok_or_else(|| {
+                handle_errors::Error::ConfigError("missing wordlist path".to_owned())
+            })?;
+            let filter = WordlistFilter::from_file(path).map_err(|e| {
+                handle_errors::Error::ConfigError(format!(
+                    "cannot read wordlist {}: {e}",
+                    path.display()
+                ))
+            })?;
+            Arc::new(filter)
+        }
+    };
+    let profanity_filter: Arc<dyn ProfanityFilter> = if config.profanity_cache_size == 0 {
+        profanity_filter
+    } else {
+        Arc::new(CachedFilter::new(
+            profanity_filter,
+            config.profanity_cache_size,
+        ))
+    };
+
+    let mailer: Arc<dyn Mailer> = match config.mailer {
+        MailerBackend::Log => Arc::new(LogMailer::new(config.mail_dir.clone())),
+        MailerBackend::Smtp => Arc::new(SmtpMailer::new(
+            &config.smtp_host,
+            config.smtp_port,
+            config.smtp_credentials.clone(),
+            &config.mail_from,
+        )?),
+    };
+
+    let breached = match &config.password.breached_passwords {
+        Some(path) => BreachedPasswords::from_file(path).map_err(|e| {
+            handle_errors::Error::ConfigError(format!(
+                "cannot read breached passwords {}: {e}",
+                path.display()
+            ))
+        })?,
+        None => BreachedPasswords::default(),
+    };
+
+    let window = Duration::from_secs(config.rate_limit_window_secs);
+    let limits = AuthLimits {
+        by_ip: RateLimiter::new(RateLimitPolicy {
+            max_requests: config.rate_limit_per_ip,
+            window,
+        }),
+        by_email: RateLimiter::new(RateLimitPolicy {
+            max_requests: config.rate_limit_per_email,
+            window,
+        }),
+        lockout: Lockout::new(LockoutPolicy {
+            threshold: config.lockout_threshold,
+            base: Duration::from_secs(config.lockout_base_secs),
+            max: Duration::from_secs(config.lockout_max_secs),
+        }),
+        trust_forwarded_for: config.trust_forwarded_for,
+    };
+
+    let store = Store::new(
+        &format!(
+            "postgres://{}:{}@{}:{}/{}",
+            config.db_user, config.db_password, config.db_host, config.db_port, config.db_name
+        ),
+        profanity_filter,
+        config.moderation,
+        Arc::new(config.keyring.clone()),
+        mailer,
+        AccountPolicy {
+            public_url: config.public_url.clone(),
+            require_verified: config.require_verified,
+            argon2: config.argon2,
+            password: config.password.clone(),
+            breached: Arc::new(breached),
+        },
+        Arc::new(limits),
+    )
+    .await
+    .map_err(handle_errors::Error::DatabaseQueryError)?;
 
     sqlx::migrate!()
         .run(&store.clone().connection)
@@ -298,3 +2304,57 @@ pub async fn setup(config: &Config) -> Result<Store, handle_errors::Error> {
 
     Ok(store)
 }
+
+#[cfg(test)]
+impl Store {
+    /// A store that only connects to the database when a query runs, for testing handlers
+    /// and filters that answer without one. Rate limits are off.
+    pub(crate) fn lazy() -> Self {
+        use crate::keyring::PasetoKey;
+        use crate::types::{Argon2Params, ModerationAction, PasswordPolicy, UnavailableAction};
+
+        Self {
+            connection: PgPoolOptions::new()
+                .connect_lazy("postgres://localhost:1/eroteme")
+                .unwrap(),
+            profanity_filter: Arc::new(WordlistFilter::new(Vec::<String>::new())),
+            moderation: ModerationPolicy {
+                title: ModerationAction::Censor,
+                content: ModerationAction::Censor,
+                flag_threshold: 3,
+                unavailable: UnavailableAction::Reject,
+            },
+            keyring: Arc::new(
+                Keyring::new(
+                    PasetoKey::new("1", "RANDOM WORDS WINTER MACINTOSH PC").unwrap(),
+                    Vec::new(),
+                )
+                .unwrap(),
+            ),
+            mailer: Arc::new(LogMailer::default()),
+            accounts: AccountPolicy {
+                public_url: "http://localhost:3030".to_owned(),
+                require_verified: false,
+                argon2: Argon2Params::default(),
+                password: PasswordPolicy::default(),
+                breached: Arc::new(BreachedPasswords::default()),
+            },
+            limits: Arc::new(AuthLimits {
+                by_ip: RateLimiter::new(RateLimitPolicy {
+                    max_requests: 0,
+                    window: Duration::ZERO,
+                }),
+                by_email: RateLimiter::new(RateLimitPolicy {
+                    max_requests: 0,
+                    window: Duration::ZERO,
+                }),
+                lockout: Lockout::new(LockoutPolicy {
+                    threshold: 0,
+                    base: Duration::ZERO,
+                    max: Duration::ZERO,
+                }),
+                trust_forwarded_for: false,
+            }),
+        }
+    }
+}
@@ -1,10 +1,21 @@
+use crate::types::{Argon2Params, BreachedPasswords, PasswordPolicy};
 use chrono::prelude::*;
+use handle_errors::Error;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Longest email address that fits a path in an SMTP command
+pub const MAX_EMAIL_LENGTH: usize = 254;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Session {
     pub exp: DateTime<Utc>,
     pub account_id: AccountId,
+    /// Login the token was issued for, checked on every request so it can be revoked
+    pub session_id: SessionId,
+    /// Tokens issued before roles existed carry no role claim and count as plain users
+    #[serde(default)]
+    pub role: Role,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -12,8 +23,223 @@ pub struct Account {
     pub id: Option<AccountId>,
     pub email: String,
     pub password: String,
+    /// Never taken from a request body, roles are only granted by an admin
+    #[serde(skip)]
+    pub role: Role,
+    /// Whether the owner confirmed the email address, never taken from a request body
+    #[serde(skip)]
+    pub verified: bool,
 }
 
 #[allow(clippy::module_name_repetitions)]
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Hash)]
 pub struct AccountId(pub i32);
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct SessionId(pub i32);
+
+/// Returned on login and refresh. The access token authorizes requests until `expires_at`,
+/// the refresh token is exchanged for a new pair and can only be used once.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Tokens {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RefreshToken {
+    pub refresh_token: String,
+}
+
+/// Permission level of an account, every role has the permissions of the roles below it
+#[derive(
+    Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, sqlx::Type,
+)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "account_role", rename_all = "snake_case")]
+pub enum Role {
+    #[default]
+    User,
+    Moderator,
+    Admin,
+}
+
+impl Role {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::Moderator => "moderator",
+            Self::Admin => "admin",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct NewRole {
+    pub role: Role,
+}
+
+/// Request for a password reset email
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ForgotPassword {
+    pub email: String,
+}
+
+/// New password, authorized by the token from a password reset email
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PasswordReset {
+    pub token: String,
+    pub password: String,
+}
+
+/// New password of a logged in account, authorized by the current one
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PasswordChange {
+    pub current_password: String,
+    pub new_password: String,
+}
+
+/// What happens to the questions, answers and comments of a deleted account
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeletedContent {
+    /// Keep them, no longer linked to the account
+    #[default]
+    Anonymize,
+    /// Delete them, along with every answer to the account's questions
+    Delete,
+}
+
+/// Request to delete the logged in account, authorized by its password
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AccountDeletion {
+    pub password: String,
+    #[serde(default)]
+    pub content: DeletedContent,
+}
+
+/// Account settings, set per deployment
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AccountPolicy {
+    /// Base URL the API is reached at, used for links in emails
+    pub public_url: String,
+    /// Only let accounts with a verified email address post
+    pub require_verified: bool,
+    /// Cost of new password hashes, older hashes are upgraded at login
+    pub argon2: Argon2Params,
+    pub password: PasswordPolicy,
+    /// Loaded from `password.breached_passwords`, empty if it isn't set
+    pub breached: Arc<BreachedPasswords>,
+}
+
+impl AccountPolicy {
+    /// Check a new password against the password policy.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the password is too short or has been breached.
+    pub fn check_password(&self, password: &str) -> Result<(), Error> {
+        self.password.check(password, &self.breached)
+    }
+}
+
+/// Trim an email address and check it looks deliverable, a local part and a domain of
+/// dot separated labels.
+///
+/// # Example usage
+/// ```rust
+/// use eroteme::types::account;
+///
+/// assert_eq!(account::validate_email(" a@b.co ").unwrap(), "a@b.co");
+/// assert!(account::validate_email("test&email.com").is_err());
+/// ```
+///
+/// # Errors
+///
+/// Will return `Err` if the address is not a valid email address.
+pub fn validate_email(email: &str) -> Result<String, Error> {
+    let email = email.trim();
+    let invalid = || Error::InvalidParameter(format!("`{email}` is not a valid email address"));
+
+    if email.len() > MAX_EMAIL_LENGTH || email.chars().any(char::is_whitespace) {
+        return Err(invalid());
+    }
+    let (local, domain) = email.rsplit_once('@').ok_or_else(invalid)?;
+    if local.is_empty() || local.len() > 64 || local.contains(['@', '"', '(', ')', '<', '>']) {
+        return Err(invalid());
+    }
+
+    let labels: Vec<&str> = domain.split('.').collect();
+    let valid_label = |label: &&str| {
+        !label.is_empty()
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+    };
+    if labels.len() < 2 || !labels.iter().all(valid_label) {
+        return Err(invalid());
+    }
+
+    Ok(email.to_owned())
+}
+
+#[cfg(test)]
+mod account_tests {
+    use super::{validate_email, Account, AccountDeletion, DeletedContent, Role, Session};
+
+    #[test]
+    fn roles_are_ordered() {
+        assert!(Role::Admin > Role::Moderator);
+        assert!(Role::Moderator > Role::User);
+    }
+
+    #[test]
+    fn session_without_role_is_user() {
+        let session: Session = serde_json::from_str(
+            r#"{"exp": "2024-07-29T12:00:00+00:00", "account_id": 1, "session_id": 1}"#,
+        )
+        .unwrap();
+        assert_eq!(session.role, Role::User);
+    }
+
+    #[test]
+    fn role_is_not_read_from_registration() {
+        let account: Account =
+            serde_json::from_str(r#"{"email": "a@b.c", "password": "secret", "role": "admin"}"#)
+                .unwrap();
+        assert_eq!(account.role, Role::User);
+        assert!(!account.verified);
+    }
+
+    #[test]
+    fn anonymize_deleted_content_by_default() {
+        let deletion: AccountDeletion = serde_json::from_str(r#"{"password": "secret"}"#).unwrap();
+        assert_eq!(deletion.content, DeletedContent::Anonymize);
+
+        let deletion: AccountDeletion =
+            serde_json::from_str(r#"{"password": "secret", "content": "delete"}"#).unwrap();
+        assert_eq!(deletion.content, DeletedContent::Delete);
+    }
+
+    #[test]
+    fn validate_email_addresses() {
+        for email in ["a@b.co", "first.last+tag@mail.example.com", "x@bücher.de"] {
+            assert_eq!(validate_email(email).unwrap(), email);
+        }
+        for email in [
+            "test&email.com",
+            "@b.co",
+            "a@",
+            "a@localhost",
+            "a@b..co",
+            "a@-b.co",
+            "a b@c.co",
+            "a@b@c.co",
+            "",
+        ] {
+            assert!(validate_email(email).is_err(), "{email} should be invalid");
+        }
+    }
+}
@@ -1,5 +1,8 @@
+use crate::types::account::AccountId;
+use crate::types::moderation::ModerationStatus;
 use crate::types::question::QuestionId;
 
+use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 
 #[allow(clippy::module_name_repetitions)]
@@ -11,11 +14,14 @@ pub struct Answer {
     pub id: AnswerId,
     pub content: String,
     pub question_id: QuestionId,
+    pub account_id: AccountId,
+    pub created_on: NaiveDateTime,
+    pub score: i32,
+    pub moderation_status: ModerationStatus,
 }
 
 #[allow(clippy::module_name_repetitions)]
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct NewAnswer {
     pub content: String,
-    pub question_id: QuestionId,
 }
@@ -0,0 +1,48 @@
+use crate::types::account::AccountId;
+use crate::types::moderation::ModerationTarget;
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+/// Privileged action recorded in the audit log
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "audit_action", rename_all = "snake_case")]
+pub enum AuditAction {
+    Approve,
+    Hide,
+    Delete,
+    SetRole,
+}
+
+/// Kind of record an audited action was taken on
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "audit_target", rename_all = "snake_case")]
+pub enum AuditTarget {
+    Question,
+    Answer,
+    Account,
+}
+
+impl From<ModerationTarget> for AuditTarget {
+    fn from(target: ModerationTarget) -> Self {
+        match target {
+            ModerationTarget::Question(_) => Self::Question,
+            ModerationTarget::Answer(_) => Self::Answer,
+        }
+    }
+}
+
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AuditEntry {
+    pub id: i32,
+    /// Moderator or admin who took the action
+    pub account_id: AccountId,
+    pub action: AuditAction,
+    pub target: AuditTarget,
+    pub target_id: i32,
+    pub details: Option<String>,
+    pub created_on: NaiveDateTime,
+}
@@ -0,0 +1,49 @@
+use crate::types::account::AccountId;
+use crate::types::answer::AnswerId;
+use crate::types::question::QuestionId;
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
+pub struct CommentId(pub i32);
+
+/// Comment on a question or an answer, `parent_id` is set for replies to another comment
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Comment {
+    pub id: CommentId,
+    pub content: String,
+    pub account_id: AccountId,
+    pub question_id: Option<QuestionId>,
+    pub answer_id: Option<AnswerId>,
+    pub parent_id: Option<CommentId>,
+    pub created_on: NaiveDateTime,
+}
+
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct NewComment {
+    pub content: String,
+    #[serde(default)]
+    pub parent_id: Option<CommentId>,
+}
+
+/// Question or answer a comment is attached to
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommentTarget {
+    Question(i32),
+    Answer(i32),
+}
+
+impl CommentTarget {
+    #[must_use]
+    pub fn of(comment: &Comment) -> Option<Self> {
+        match (&comment.question_id, &comment.answer_id) {
+            (Some(question_id), None) => Some(Self::Question(question_id.0)),
+            (None, Some(answer_id)) => Some(Self::Answer(answer_id.0)),
+            _ => None,
+        }
+    }
+}
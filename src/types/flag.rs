@@ -0,0 +1,68 @@
+use crate::types::account::AccountId;
+use crate::types::answer::AnswerId;
+use crate::types::moderation::ModerationStatus;
+use crate::types::question::QuestionId;
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::{PgHasArrayType, PgTypeInfo};
+
+/// Why a question or answer was reported
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "flag_reason", rename_all = "snake_case")]
+pub enum FlagReason {
+    Spam,
+    Abuse,
+    OffTopic,
+    Other,
+}
+
+impl PgHasArrayType for FlagReason {
+    fn array_type_info() -> PgTypeInfo {
+        PgTypeInfo::with_name("_flag_reason")
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Flag {
+    pub id: i32,
+    pub account_id: AccountId,
+    pub question_id: Option<QuestionId>,
+    pub answer_id: Option<AnswerId>,
+    pub reason: FlagReason,
+    pub created_on: NaiveDateTime,
+}
+
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct NewFlag {
+    pub reason: FlagReason,
+}
+
+/// Question or answer with open flags, as listed for moderators
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FlaggedPost {
+    pub question_id: Option<QuestionId>,
+    pub answer_id: Option<AnswerId>,
+    pub flags: i64,
+    pub reasons: Vec<FlagReason>,
+    pub moderation_status: ModerationStatus,
+    pub last_flagged_on: NaiveDateTime,
+}
+
+#[cfg(test)]
+mod flag_tests {
+    use super::{FlagReason, NewFlag};
+
+    #[test]
+    fn parse_reason() {
+        let flag: NewFlag = serde_json::from_str(r#"{"reason": "off_topic"}"#).unwrap();
+        assert_eq!(flag.reason, FlagReason::OffTopic);
+    }
+
+    #[test]
+    fn unknown_reason() {
+        assert!(serde_json::from_str::<NewFlag>(r#"{"reason": "boring"}"#).is_err());
+    }
+}
@@ -1,11 +1,44 @@
 pub mod account;
-pub use account::{Account, AccountId, Session};
+pub use account::{
+    Account, AccountDeletion, AccountId, AccountPolicy, DeletedContent, ForgotPassword, NewRole,
+    PasswordChange, PasswordReset, RefreshToken, Role, Session, SessionId, Tokens,
+};
+
+pub mod audit;
+pub use audit::{AuditAction, AuditEntry, AuditTarget};
 
 pub mod answer;
 pub use answer::{Answer, AnswerId, NewAnswer};
 
+pub mod comment;
+pub use comment::{Comment, CommentId, CommentTarget, NewComment};
+
+pub mod flag;
+pub use flag::{Flag, FlagReason, FlaggedPost, NewFlag};
+
+pub mod moderation;
+pub use moderation::{
+    ModeratedField, ModerationAction, ModerationPolicy, ModerationQueue, ModerationStatus,
+    ModerationTarget, UnavailableAction,
+};
+
 pub mod pagination;
 pub use pagination::Pagination;
 
+pub mod password;
+pub use password::{Argon2Params, BreachedPasswords, PasswordPolicy};
+
+pub mod query;
+pub use query::{AnswerSort, QuestionQuery, QuestionSort};
+
 pub mod question;
-pub use question::{NewQuestion, Question, QuestionId};
+pub use question::{NewQuestion, Question, QuestionId, QuestionWithAnswers};
+
+pub mod search;
+pub use search::SearchResult;
+
+pub mod tag;
+pub use tag::{Tag, TagFilter, TagMode};
+
+pub mod vote;
+pub use vote::{Score, Vote, VoteValue};
@@ -0,0 +1,303 @@
+use crate::profanity::Censored;
+use crate::types::{Answer, Question};
+use clap::{Args, ValueEnum};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Whether a post is publicly visible, waiting for a moderator or hidden by one, or was
+/// written while the profanity filter was unavailable and waits to be checked
+#[derive(
+    Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, sqlx::Type,
+)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "moderation_status", rename_all = "snake_case")]
+pub enum ModerationStatus {
+    #[default]
+    Approved,
+    PendingReview,
+    Hidden,
+    Unchecked,
+}
+
+/// Question or answer a moderator acts on
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ModerationTarget {
+    Question(i32),
+    Answer(i32),
+}
+
+impl fmt::Display for ModerationTarget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Question(id) => write!(f, "Question {id}"),
+            Self::Answer(id) => write!(f, "Answer {id}"),
+        }
+    }
+}
+
+impl ModerationTarget {
+    #[must_use]
+    pub const fn id(self) -> i32 {
+        match self {
+            Self::Question(id) | Self::Answer(id) => id,
+        }
+    }
+}
+
+/// Posts waiting for a moderator, oldest first
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ModerationQueue {
+    pub questions: Vec<Question>,
+    pub answers: Vec<Answer>,
+}
+
+/// What to do with a field that contains profanity
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ModerationAction {
+    /// Replace profane words with `*`
+    #[default]
+    Censor,
+    /// Refuse the post and list the offending words
+    Reject,
+    /// Keep the post as written but hide it until a moderator reviews it
+    Review,
+}
+
+/// What to do with a post when the profanity filter is unavailable
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnavailableAction {
+    /// Refuse the post with `503 Service Unavailable` and a `Retry-After` header
+    #[default]
+    Reject,
+    /// Keep the post hidden as written until the profanity filter can check it
+    Queue,
+}
+
+/// Field of a question or answer the moderation policy is applied to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ModeratedField {
+    Title,
+    Content,
+}
+
+impl fmt::Display for ModeratedField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Title => write!(f, "title"),
+            Self::Content => write!(f, "content"),
+        }
+    }
+}
+
+/// Moderation settings, set per deployment
+#[derive(Args, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ModerationPolicy {
+    /// What to do with profanity in question titles (censor, reject or review)
+    #[clap(long = "moderate-title", value_enum, default_value = "censor")]
+    pub title: ModerationAction,
+    /// What to do with profanity in question and answer content (censor, reject or review)
+    #[clap(long = "moderate-content", value_enum, default_value = "censor")]
+    pub content: ModerationAction,
+    /// Number of open flags after which a question or answer is hidden
+    #[clap(long, default_value = "3")]
+    pub flag_threshold: i64,
+    /// What to do with posts while the profanity filter is unavailable (reject or queue)
+    #[clap(long = "profanity-unavailable", value_enum, default_value = "reject")]
+    pub unavailable: UnavailableAction,
+}
+
+impl ModerationPolicy {
+    #[must_use]
+    pub const fn action(&self, field: ModeratedField) -> ModerationAction {
+        match field {
+            ModeratedField::Title => self.title,
+            ModeratedField::Content => self.content,
+        }
+    }
+
+    /// Moderate a field from the result of its profanity check, queueing it for a later
+    /// check if the filter is unavailable and the policy allows it.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the policy rejects the content or the profanity check failed
+    /// and can't be queued.
+    pub fn apply(
+        &self,
+        field: ModeratedField,
+        original: String,
+        censored: Result<Censored, handle_errors::Error>,
+    ) -> Result<Moderated, handle_errors::Error> {
+        match censored {
+            Ok(censored) => self.action(field).apply(field, original, censored),
+            Err(handle_errors::Error::ProfanityUnavailable(_))
+                if self.unavailable == UnavailableAction::Queue =>
+            {
+                Ok(Moderated {
+                    content: original,
+                    status: ModerationStatus::Unchecked,
+                })
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Field content after the moderation policy was applied
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Moderated {
+    pub content: String,
+    pub status: ModerationStatus,
+}
+
+impl ModerationAction {
+    /// Decide what to store for a field given the original content and the result of the
+    /// profanity check.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` listing the offending words if the action is `Reject` and the
+    /// content contains profanity.
+    pub fn apply(
+        self,
+        field: ModeratedField,
+        original: String,
+        censored: Censored,
+    ) -> Result<Moderated, handle_errors::Error> {
+        if censored.is_clean() {
+            return Ok(Moderated {
+                content: original,
+                status: ModerationStatus::Approved,
+            });
+        }
+
+        match self {
+            Self::Censor => Ok(Moderated {
+                content: censored.content,
+                status: ModerationStatus::Approved,
+            }),
+            Self::Reject => {
+                let mut words: Vec<String> = censored
+                    .bad_words
+                    .into_iter()
+                    .map(|bad_word| bad_word.original)
+                    .collect();
+                words.sort();
+                words.dedup();
+                Err(handle_errors::Error::ProfaneContent(
+                    field.to_string(),
+                    words,
+                ))
+            }
+            Self::Review => Ok(Moderated {
+                content: original,
+                status: ModerationStatus::PendingReview,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod moderation_tests {
+    use super::UnavailableAction;
+    use super::{ModeratedField, ModerationAction, ModerationPolicy, ModerationStatus};
+    use crate::profanity::{BadWord, Censored};
+
+    fn censored() -> Censored {
+        let bad_word = BadWord {
+            original: "shitty".to_owned(),
+            word: "shitty".to_owned(),
+            deviations: 0,
+            info: 0,
+            replaced_len: 6,
+            start: 2,
+            end: 8,
+        };
+        Censored {
+            content: "A ****** day, a ****** title".to_owned(),
+            bad_words: vec![bad_word.clone(), bad_word],
+        }
+    }
+
+    const ORIGINAL: &str = "A shitty day, a shitty title";
+
+    #[test]
+    fn censor_profanity() {
+        let moderated = ModerationAction::Censor
+            .apply(ModeratedField::Title, ORIGINAL.to_owned(), censored())
+            .unwrap();
+        assert_eq!(moderated.content, "A ****** day, a ****** title");
+        assert_eq!(moderated.status, ModerationStatus::Approved);
+    }
+
+    #[test]
+    fn reject_profanity() {
+        let err = ModerationAction::Reject
+            .apply(ModeratedField::Title, ORIGINAL.to_owned(), censored())
+            .unwrap_err();
+        assert_eq!(err.to_string(), "title contains profanity: shitty");
+    }
+
+    #[test]
+    fn queue_profanity_for_review() {
+        let moderated = ModerationAction::Review
+            .apply(ModeratedField::Content, ORIGINAL.to_owned(), censored())
+            .unwrap();
+        assert_eq!(moderated.content, ORIGINAL);
+        assert_eq!(moderated.status, ModerationStatus::PendingReview);
+    }
+
+    #[test]
+    fn approve_clean_content() {
+        for action in [
+            ModerationAction::Censor,
+            ModerationAction::Reject,
+            ModerationAction::Review,
+        ] {
+            let content = "A lovely day".to_owned();
+            let moderated = action
+                .apply(
+                    ModeratedField::Content,
+                    content.clone(),
+                    Censored::clean(content.clone()),
+                )
+                .unwrap();
+            assert_eq!(moderated.content, content);
+            assert_eq!(moderated.status, ModerationStatus::Approved);
+        }
+    }
+
+    #[test]
+    fn queue_while_unavailable() {
+        let mut policy = ModerationPolicy {
+            title: ModerationAction::Censor,
+            content: ModerationAction::Censor,
+            flag_threshold: 3,
+            unavailable: UnavailableAction::Queue,
+        };
+        let unavailable = || Err(handle_errors::Error::ProfanityUnavailable(30));
+
+        let moderated = policy
+            .apply(ModeratedField::Content, ORIGINAL.to_owned(), unavailable())
+            .unwrap();
+        assert_eq!(moderated.content, ORIGINAL);
+        assert_eq!(moderated.status, ModerationStatus::Unchecked);
+
+        policy.unavailable = UnavailableAction::Reject;
+        let err = policy
+            .apply(ModeratedField::Content, ORIGINAL.to_owned(), unavailable())
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            handle_errors::Error::ProfanityUnavailable(30)
+        ));
+    }
+
+    #[test]
+    fn serialize_status() {
+        assert_eq!(
+            serde_json::to_string(&ModerationStatus::PendingReview).unwrap(),
+            r#""pending_review""#
+        );
+    }
+}
@@ -1,6 +1,15 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, NaiveDateTime};
 use handle_errors::Error;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// Number of items returned when the client doesn't ask for a page size
+pub const DEFAULT_PAGE_SIZE: i32 = 20;
+/// Largest page size the server will return, bigger limits are clamped to this
+pub const MAX_PAGE_SIZE: i32 = 100;
+
 /// Pagination struct that is getting extracted from query params
 #[derive(Debug, Default, PartialEq, Eq)]
 pub struct Pagination {
@@ -15,7 +24,7 @@ pub struct Pagination {
 /// # Example query
 /// GET requests to this route can have a pagination attached so we just
 /// return the questions we need
-/// `/questions?start=1&end=10`
+/// `/questions?limit=10&offset=1`
 ///
 /// # Example usage
 /// ```rust
@@ -33,37 +42,198 @@ pub struct Pagination {
 ///
 /// # Errors
 ///
-/// Will return `Err` if `limit` or `offset` parameters are missing.
+/// Will return `Err` if `limit` or `offset` parameters are missing or negative.
 #[allow(clippy::missing_panics_doc, clippy::module_name_repetitions)]
 pub fn extract_pagination<S: ::std::hash::BuildHasher>(
     params: &HashMap<String, String, S>,
 ) -> Result<Pagination, Error> {
-    // TODO: handle start greater than end
     if params.contains_key("limit") && params.contains_key("offset") {
+        // Takes the `limit` parameter and tries to convert it to a number
+        let limit = params
+            .get("limit")
+            .expect("limit param not set in map")
+            .parse::<i32>()
+            .map_err(Error::ParseError)?;
+        // Takes the `offset` parameter and tries to convert it to a number
+        let offset = params
+            .get("offset")
+            .expect("offset param not set in map")
+            .parse::<i32>()
+            .map_err(Error::ParseError)?;
+
         return Ok(Pagination {
-            // Takes the `limit` parameter and tries to convert it to a number
-            limit: Some(
-                params
-                    .get("limit")
-                    .expect("limit param not set in map")
-                    .parse::<i32>()
-                    .map_err(Error::ParseError)?,
-            ),
-            // Takes the `offset` parameter and tries to convert it to a number
-            offset: params
-                .get("offset")
-                .expect("offset param not set in map")
-                .parse::<i32>()
-                .map_err(Error::ParseError)?,
+            limit: Some(validate_limit(limit)?),
+            offset: validate_offset(offset)?,
         });
     }
 
     Err(Error::MissingParameters)
 }
 
+/// Position in a listing ordered by `(key, created_on, id)`, handed to clients as an opaque
+/// string. `key` is only set for listings sorted on something other than time.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cursor {
+    pub key: Option<i64>,
+    pub created_on: NaiveDateTime,
+    pub id: i32,
+}
+
+impl Cursor {
+    #[must_use]
+    pub fn encode(&self) -> String {
+        let micros = self.created_on.and_utc().timestamp_micros();
+        let raw = match self.key {
+            Some(key) => format!("{key}:{micros}:{}", self.id),
+            None => format!("{micros}:{}", self.id),
+        };
+        URL_SAFE_NO_PAD.encode(raw)
+    }
+
+    /// # Errors
+    ///
+    /// Will return `Err` if the cursor was not produced by `Cursor::encode`.
+    pub fn decode(cursor: &str) -> Result<Self, Error> {
+        let invalid = || Error::InvalidParameter("invalid cursor".to_owned());
+
+        let raw = URL_SAFE_NO_PAD.decode(cursor).map_err(|_| invalid())?;
+        let raw = String::from_utf8(raw).map_err(|_| invalid())?;
+        let parts = raw.split(':').collect::<Vec<_>>();
+        let (key, micros, id) = match parts.as_slice() {
+            [micros, id] => (None, micros, id),
+            [key, micros, id] => (Some(key.parse::<i64>().map_err(|_| invalid())?), micros, id),
+            _ => return Err(invalid()),
+        };
+
+        let micros = micros.parse::<i64>().map_err(|_| invalid())?;
+        let created_on = DateTime::from_timestamp_micros(micros)
+            .ok_or_else(invalid)?
+            .naive_utc();
+        let id = id.parse::<i32>().map_err(|_| invalid())?;
+
+        Ok(Self {
+            key,
+            created_on,
+            id,
+        })
+    }
+}
+
+/// Page request for listings that respond with a `Page` envelope
+#[derive(Debug, PartialEq, Eq)]
+pub struct PageRequest {
+    /// Only return items that come after this cursor
+    pub after: Option<Cursor>,
+    /// Number of items on the page, never more than `MAX_PAGE_SIZE`
+    pub limit: i32,
+    /// Number of items to skip, kept for clients that still page by offset
+    pub offset: i32,
+}
+
+impl Default for PageRequest {
+    fn default() -> Self {
+        Self {
+            after: None,
+            limit: DEFAULT_PAGE_SIZE,
+            offset: 0,
+        }
+    }
+}
+
+/// Extract cursor pagination from query params
+///
+/// # Example query
+/// `/questions?limit=10` for the first page, then `/questions?after=<next_cursor>&limit=10`
+///
+/// # Errors
+///
+/// Will return `Err` if a parameter cannot be parsed, `limit` is not positive, `offset` is
+/// negative, or `after` is combined with `offset`.
+pub fn extract_page_request<S: ::std::hash::BuildHasher>(
+    params: &HashMap<String, String, S>,
+) -> Result<PageRequest, Error> {
+    let mut page = PageRequest::default();
+
+    if let Some(limit) = params.get("limit") {
+        page.limit = validate_limit(limit.parse::<i32>().map_err(Error::ParseError)?)?;
+        if page.limit == 0 {
+            return Err(Error::InvalidParameter(
+                "limit must be at least 1".to_owned(),
+            ));
+        }
+    }
+
+    if let Some(offset) = params.get("offset") {
+        page.offset = validate_offset(offset.parse::<i32>().map_err(Error::ParseError)?)?;
+    }
+
+    if let Some(after) = params.get("after") {
+        if page.offset > 0 {
+            return Err(Error::InvalidParameter(
+                "`after` cannot be combined with `offset`".to_owned(),
+            ));
+        }
+        page.after = Some(Cursor::decode(after)?);
+    }
+
+    Ok(page)
+}
+
+/// Response envelope for paginated listings
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Cursor to pass as `after` to fetch the next page
+    pub next_cursor: Option<String>,
+    pub has_more: bool,
+}
+
+impl<T> Page<T> {
+    /// Build a page from up to `limit + 1` rows, the extra row only signals that there are
+    /// more items to fetch.
+    #[must_use]
+    pub fn new(mut items: Vec<T>, limit: i32, cursor: impl Fn(&T) -> Cursor) -> Self {
+        let limit = usize::try_from(limit).unwrap_or_default();
+        let has_more = items.len() > limit;
+        items.truncate(limit);
+
+        let next_cursor = if has_more {
+            items.last().map(|item| cursor(item).encode())
+        } else {
+            None
+        };
+
+        Self {
+            items,
+            next_cursor,
+            has_more,
+        }
+    }
+}
+
+fn validate_limit(limit: i32) -> Result<i32, Error> {
+    if limit < 0 {
+        return Err(Error::InvalidParameter(
+            "limit cannot be negative".to_owned(),
+        ));
+    }
+    Ok(limit.min(MAX_PAGE_SIZE))
+}
+
+fn validate_offset(offset: i32) -> Result<i32, Error> {
+    if offset < 0 {
+        return Err(Error::InvalidParameter(
+            "offset cannot be negative".to_owned(),
+        ));
+    }
+    Ok(offset)
+}
+
 #[cfg(test)]
 mod pagination_tests {
-    use super::{extract_pagination, Error, HashMap, Pagination};
+    use super::{extract_page_request, extract_pagination, Error, HashMap, Pagination};
+    use super::{Cursor, Page, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE};
+    use chrono::NaiveDate;
 
     #[test]
     fn valid_pagination() {
@@ -115,4 +285,106 @@ mod pagination_tests {
         let expected = "cannot parse parameter: invalid digit found in string".to_owned();
         assert_eq!(pagination_result, expected);
     }
+
+    #[test]
+    fn negative_offset() {
+        let mut params = HashMap::new();
+        params.insert(String::from("limit"), String::from("1"));
+        params.insert(String::from("offset"), String::from("-1"));
+        let pagination_result = format!("{}", extract_pagination(&params).unwrap_err());
+        let expected = "invalid parameter: offset cannot be negative".to_owned();
+        assert_eq!(pagination_result, expected);
+    }
+
+    #[test]
+    fn limit_is_clamped() {
+        let mut params = HashMap::new();
+        params.insert(String::from("limit"), String::from("100000"));
+        params.insert(String::from("offset"), String::from("0"));
+        let pagination_result = extract_pagination(&params).unwrap();
+        assert_eq!(pagination_result.limit, Some(MAX_PAGE_SIZE));
+    }
+
+    fn cursor() -> Cursor {
+        Cursor {
+            key: None,
+            created_on: NaiveDate::from_ymd_opt(2024, 6, 13)
+                .unwrap()
+                .and_hms_micro_opt(14, 30, 19, 123_456)
+                .unwrap(),
+            id: 42,
+        }
+    }
+
+    #[test]
+    fn cursor_roundtrip() {
+        let encoded = cursor().encode();
+        assert_eq!(Cursor::decode(&encoded).unwrap(), cursor());
+    }
+
+    #[test]
+    fn keyed_cursor_roundtrip() {
+        let cursor = Cursor {
+            key: Some(-3),
+            ..cursor()
+        };
+        assert_eq!(Cursor::decode(&cursor.encode()).unwrap(), cursor);
+    }
+
+    #[test]
+    fn invalid_cursor() {
+        let cursor_result = format!("{}", Cursor::decode("not a cursor").unwrap_err());
+        let expected = "invalid parameter: invalid cursor".to_owned();
+        assert_eq!(cursor_result, expected);
+    }
+
+    #[test]
+    fn default_page_request() {
+        let page = extract_page_request(&HashMap::new()).unwrap();
+        assert_eq!(page.limit, DEFAULT_PAGE_SIZE);
+        assert_eq!(page.after, None);
+    }
+
+    #[test]
+    fn page_request_with_cursor() {
+        let mut params = HashMap::new();
+        params.insert(String::from("after"), cursor().encode());
+        params.insert(String::from("limit"), String::from("5"));
+        let page = extract_page_request(&params).unwrap();
+        assert_eq!(page.after, Some(cursor()));
+        assert_eq!(page.limit, 5);
+    }
+
+    #[test]
+    fn cursor_with_offset() {
+        let mut params = HashMap::new();
+        params.insert(String::from("after"), cursor().encode());
+        params.insert(String::from("offset"), String::from("5"));
+        assert!(extract_page_request(&params).is_err());
+    }
+
+    #[test]
+    fn page_has_more() {
+        let items = vec![1, 2, 3];
+        let page = Page::new(items, 2, |id| Cursor {
+            id: *id,
+            ..cursor()
+        });
+        assert_eq!(page.items, vec![1, 2]);
+        assert!(page.has_more);
+        assert_eq!(
+            page.next_cursor,
+            Some(Cursor { id: 2, ..cursor() }.encode())
+        );
+    }
+
+    #[test]
+    fn last_page() {
+        let page = Page::new(vec![1, 2], 2, |id| Cursor {
+            id: *id,
+            ..cursor()
+        });
+        assert!(!page.has_more);
+        assert_eq!(page.next_cursor, None);
+    }
 }
@@ -0,0 +1,241 @@
+use clap::Args;
+use handle_errors::Error;
+use sha1::{Digest, Sha1};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+/// Length of the SHA-1 prefix breached passwords are grouped by, as in the Pwned Passwords
+/// range API
+const PREFIX_LEN: usize = 5;
+
+/// Argon2id cost parameters for new password hashes, set per deployment
+#[derive(Args, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Argon2Params {
+    /// Memory used to hash a password, in KiB
+    #[clap(long = "argon2-memory-kib", default_value = "19456")]
+    pub memory_kib: u32,
+    /// Number of passes over the memory
+    #[clap(long = "argon2-iterations", default_value = "2")]
+    pub iterations: u32,
+    /// Number of lanes hashed in parallel
+    #[clap(long = "argon2-parallelism", default_value = "1")]
+    pub parallelism: u32,
+}
+
+impl Default for Argon2Params {
+    /// The OWASP recommended minimum, same as `argon2::Config::default()`
+    fn default() -> Self {
+        Self {
+            memory_kib: 19 * 1024,
+            iterations: 2,
+            parallelism: 1,
+        }
+    }
+}
+
+impl Argon2Params {
+    /// # Errors
+    ///
+    /// Will return `Err` if argon2 can't hash with these parameters.
+    pub fn validate(&self) -> Result<(), Error> {
+        argon2::hash_encoded(b"password", &[0; 16], &self.config())
+            .map(|_| ())
+            .map_err(|e| Error::ConfigError(format!("invalid argon2 parameters: {e}")))
+    }
+
+    #[must_use]
+    pub fn config(&self) -> argon2::Config<'static> {
+        argon2::Config {
+            variant: argon2::Variant::Argon2id,
+            version: argon2::Version::Version13,
+            mem_cost: self.memory_kib,
+            time_cost: self.iterations,
+            lanes: self.parallelism,
+            ..argon2::Config::default()
+        }
+    }
+
+    /// Whether an encoded hash was made with another variant or weaker parameters, and
+    /// should be replaced the next time the password is known.
+    ///
+    /// # Example usage
+    /// ```rust
+    /// use eroteme::types::password::Argon2Params;
+    ///
+    /// let params = Argon2Params::default();
+    /// assert!(params.needs_rehash("$argon2i$v=19$m=4096,t=3,p=1$c2FsdA$aGFzaA"));
+    /// assert!(!params.needs_rehash("$argon2id$v=19$m=19456,t=2,p=1$c2FsdA$aGFzaA"));
+    /// ```
+    #[must_use]
+    pub fn needs_rehash(&self, encoded: &str) -> bool {
+        let mut parts = encoded.split('$').skip(1);
+        let (Some("argon2id"), Some("v=19"), Some(costs)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            return true;
+        };
+
+        let mut memory_kib = 0;
+        let mut iterations = 0;
+        let mut parallelism = 0;
+        for cost in costs.split(',') {
+            let value = |v: &str| v.parse::<u32>().unwrap_or(0);
+            match cost.split_once('=') {
+                Some(("m", v)) => memory_kib = value(v),
+                Some(("t", v)) => iterations = value(v),
+                Some(("p", v)) => parallelism = value(v),
+                _ => {}
+            }
+        }
+
+        memory_kib < self.memory_kib
+            || iterations < self.iterations
+            || parallelism < self.parallelism
+    }
+}
+
+/// Rules new passwords have to follow, set per deployment
+#[derive(Args, Clone, Debug, PartialEq, Eq)]
+pub struct PasswordPolicy {
+    /// Minimum number of characters in a password
+    #[clap(long = "password-min-length", default_value = "8")]
+    pub min_length: usize,
+    /// File of SHA-1 hashes of breached passwords, one `HASH[:COUNT]` per line, as in the
+    /// Pwned Passwords downloads
+    #[clap(long)]
+    pub breached_passwords: Option<PathBuf>,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: 8,
+            breached_passwords: None,
+        }
+    }
+}
+
+/// SHA-1 hashes of breached passwords, grouped by their first five hex characters. A lookup
+/// only needs the prefix group of the password's hash, so the list can be swapped for a
+/// range API without changing callers.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BreachedPasswords {
+    ranges: HashMap<String, HashSet<String>>,
+}
+
+impl BreachedPasswords {
+    #[must_use]
+    pub fn new<I, S>(hashes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut ranges: HashMap<String, HashSet<String>> = HashMap::new();
+        for line in hashes {
+            let hash = line.as_ref().split(':').next().unwrap_or_default().trim();
+            if hash.len() != 40 || !hash.is_ascii() {
+                continue;
+            }
+            let hash = hash.to_ascii_uppercase();
+            let (prefix, suffix) = hash.split_at(PREFIX_LEN);
+            ranges
+                .entry(prefix.to_owned())
+                .or_default()
+                .insert(suffix.to_owned());
+        }
+        Self { ranges }
+    }
+
+    /// # Errors
+    ///
+    /// Will return `Err` if the file cannot be read.
+    pub fn from_file(path: &Path) -> io::Result<Self> {
+        Ok(Self::new(fs::read_to_string(path)?.lines()))
+    }
+
+    #[must_use]
+    pub fn contains(&self, password: &str) -> bool {
+        let hash = format!("{:X}", Sha1::digest(password.as_bytes()));
+        let (prefix, suffix) = hash.split_at(PREFIX_LEN);
+        self.ranges
+            .get(prefix)
+            .is_some_and(|range| range.contains(suffix))
+    }
+}
+
+impl PasswordPolicy {
+    /// # Errors
+    ///
+    /// Will return `Err` if the password is too short or has been breached.
+    pub fn check(&self, password: &str, breached: &BreachedPasswords) -> Result<(), Error> {
+        if password.chars().count() < self.min_length {
+            return Err(Error::WeakPassword(format!(
+                "must be at least {} characters long",
+                self.min_length
+            )));
+        }
+        if breached.contains(password) {
+            return Err(Error::WeakPassword(
+                "appears in a list of breached passwords, choose another one".to_owned(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod password_tests {
+    use super::{Argon2Params, BreachedPasswords, PasswordPolicy};
+
+    // SHA-1 of `password`
+    const BREACHED: &str = "5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8:10437277";
+
+    #[test]
+    fn reject_short_passwords() {
+        let policy = PasswordPolicy::default();
+        let err = policy
+            .check("short", &BreachedPasswords::default())
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "password is too weak: must be at least 8 characters long"
+        );
+        assert!(policy
+            .check("long enough", &BreachedPasswords::default())
+            .is_ok());
+    }
+
+    #[test]
+    fn reject_breached_passwords() {
+        let breached = BreachedPasswords::new(["# comment", BREACHED, "not a hash"]);
+        let policy = PasswordPolicy::default();
+
+        assert!(policy.check("password", &breached).is_err());
+        assert!(policy.check("correct horse", &breached).is_ok());
+    }
+
+    #[test]
+    fn rehash_weaker_hashes() {
+        let params = Argon2Params::default();
+        let stronger = Argon2Params {
+            memory_kib: 32 * 1024,
+            ..params
+        };
+
+        let hash = argon2::hash_encoded(b"password", &[0; 16], &params.config()).unwrap();
+        assert!(!params.needs_rehash(&hash));
+        assert!(stronger.needs_rehash(&hash));
+        assert!(params.needs_rehash("not a hash"));
+    }
+
+    #[test]
+    fn validate_params() {
+        assert!(Argon2Params::default().validate().is_ok());
+        let params = Argon2Params {
+            parallelism: 0,
+            ..Argon2Params::default()
+        };
+        assert!(params.validate().is_err());
+    }
+}
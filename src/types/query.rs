@@ -0,0 +1,265 @@
+use crate::types::account::AccountId;
+use crate::types::pagination::{self, PageRequest};
+use crate::types::tag::{self, TagFilter};
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use handle_errors::Error;
+use std::collections::HashMap;
+
+/// Order in which questions are listed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum QuestionSort {
+    /// Most recently asked first
+    #[default]
+    Newest,
+    /// Least recently asked first
+    Oldest,
+    /// Questions with the most answers first
+    MostAnswered,
+    /// Only questions without answers, most recently asked first
+    Unanswered,
+    /// Highest voted questions first
+    Score,
+}
+
+impl QuestionSort {
+    fn parse(value: &str) -> Result<Self, Error> {
+        match value {
+            "newest" => Ok(Self::Newest),
+            "oldest" => Ok(Self::Oldest),
+            "most_answered" => Ok(Self::MostAnswered),
+            "unanswered" => Ok(Self::Unanswered),
+            "score" => Ok(Self::Score),
+            _ => Err(Error::InvalidParameter(format!(
+                "sort must be one of `newest`, `oldest`, `most_answered`, `unanswered` or `score`, got `{value}`"
+            ))),
+        }
+    }
+}
+
+/// Order in which the answers to a question are listed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AnswerSort {
+    /// Answers in the order they were posted
+    #[default]
+    Oldest,
+    /// Highest voted answers first
+    Score,
+}
+
+/// Sorting, filtering and pagination options for the `/questions` route
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct QuestionQuery {
+    pub sort: QuestionSort,
+    /// Only questions asked by this account
+    pub author: Option<AccountId>,
+    /// Only questions created at or after this time
+    pub created_after: Option<NaiveDateTime>,
+    /// Only questions created before this time
+    pub created_before: Option<NaiveDateTime>,
+    /// Only questions with (`true`) or without (`false`) an accepted answer
+    pub accepted: Option<bool>,
+    pub tags: Option<TagFilter>,
+    pub page: PageRequest,
+}
+
+/// Extract query parameters from the `/questions` route
+///
+/// # Example query
+/// `/questions?sort=most_answered&author=3&created_after=2024-06-01&accepted=false&tag=rust`
+///
+/// Dates are either RFC 3339 timestamps or plain `YYYY-MM-DD` dates, which stand for
+/// midnight UTC at the start of that day.
+///
+/// # Example usage
+/// ```rust
+/// use eroteme::types::query::{self, QuestionSort};
+///
+/// let params = vec![("sort".to_string(), "oldest".to_string())];
+/// let q = query::extract_question_query(&params).unwrap();
+///
+/// assert_eq!(q.sort, QuestionSort::Oldest);
+/// assert_eq!(q.author, None);
+/// ```
+///
+/// # Errors
+///
+/// Will return `Err` if a parameter cannot be parsed or `created_after` is later than
+/// `created_before`.
+#[allow(clippy::module_name_repetitions)]
+pub fn extract_question_query(params: &[(String, String)]) -> Result<QuestionQuery, Error> {
+    let mut query = QuestionQuery {
+        tags: tag::extract_tag_filter(params)?,
+        ..QuestionQuery::default()
+    };
+    let mut page_params = HashMap::new();
+
+    for (key, value) in params {
+        match key.as_str() {
+            "sort" => query.sort = QuestionSort::parse(value)?,
+            "author" => {
+                query.author = Some(AccountId(value.parse::<i32>().map_err(Error::ParseError)?));
+            }
+            "created_after" => query.created_after = Some(parse_date("created_after", value)?),
+            "created_before" => query.created_before = Some(parse_date("created_before", value)?),
+            "accepted" => {
+                query.accepted = Some(value.parse::<bool>().map_err(|_| {
+                    Error::InvalidParameter(format!(
+                        "accepted must be `true` or `false`, got `{value}`"
+                    ))
+                })?);
+            }
+            "after" | "limit" | "offset" => {
+                page_params.insert(key.clone(), value.clone());
+            }
+            _ => {}
+        }
+    }
+
+    if let (Some(after), Some(before)) = (query.created_after, query.created_before) {
+        if after > before {
+            return Err(Error::InvalidParameter(
+                "created_after must not be later than created_before".to_owned(),
+            ));
+        }
+    }
+
+    query.page = pagination::extract_page_request(&page_params)?;
+
+    Ok(query)
+}
+
+/// Extract the sort order from the `/questions/:question_id/answers` route
+///
+/// # Example query
+/// `/questions/1/answers?sort=score`
+///
+/// # Errors
+///
+/// Will return `Err` if `sort` is neither `oldest` nor `score`.
+pub fn extract_answer_sort<S: ::std::hash::BuildHasher>(
+    params: &HashMap<String, String, S>,
+) -> Result<AnswerSort, Error> {
+    match params.get("sort").map(String::as_str) {
+        None | Some("oldest") => Ok(AnswerSort::Oldest),
+        Some("score") => Ok(AnswerSort::Score),
+        Some(value) => Err(Error::InvalidParameter(format!(
+            "sort must be `oldest` or `score`, got `{value}`"
+        ))),
+    }
+}
+
+fn parse_date(name: &str, value: &str) -> Result<NaiveDateTime, Error> {
+    if let Ok(date_time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(date_time.naive_utc());
+    }
+
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .ok_or_else(|| {
+            Error::InvalidParameter(format!(
+                "{name} must be an RFC 3339 timestamp or a YYYY-MM-DD date, got `{value}`"
+            ))
+        })
+}
+
+#[cfg(test)]
+mod query_tests {
+    use super::{extract_answer_sort, extract_question_query, AccountId, AnswerSort, QuestionSort};
+    use chrono::NaiveDate;
+    use std::collections::HashMap;
+
+    fn pairs(params: &[(&str, &str)]) -> Vec<(String, String)> {
+        params
+            .iter()
+            .map(|(k, v)| ((*k).to_owned(), (*v).to_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn defaults() {
+        let query = extract_question_query(&[]).unwrap();
+        assert_eq!(query.sort, QuestionSort::Newest);
+        assert_eq!(query.tags, None);
+    }
+
+    #[test]
+    fn sort_and_author() {
+        let params = pairs(&[("sort", "most_answered"), ("author", "3")]);
+        let query = extract_question_query(&params).unwrap();
+        assert_eq!(query.sort, QuestionSort::MostAnswered);
+        assert_eq!(query.author, Some(AccountId(3)));
+    }
+
+    #[test]
+    fn accepted_filter() {
+        let params = pairs(&[("accepted", "true")]);
+        let query = extract_question_query(&params).unwrap();
+        assert_eq!(query.accepted, Some(true));
+
+        let params = pairs(&[("accepted", "yes")]);
+        assert!(extract_question_query(&params).is_err());
+    }
+
+    #[test]
+    fn invalid_sort() {
+        let params = pairs(&[("sort", "random")]);
+        assert!(extract_question_query(&params).is_err());
+    }
+
+    #[test]
+    fn date_filters() {
+        let params = pairs(&[
+            ("created_after", "2024-06-01"),
+            ("created_before", "2024-06-13T10:30:00+02:00"),
+        ]);
+        let query = extract_question_query(&params).unwrap();
+        assert_eq!(
+            query.created_after,
+            NaiveDate::from_ymd_opt(2024, 6, 1)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+        );
+        assert_eq!(
+            query.created_before,
+            NaiveDate::from_ymd_opt(2024, 6, 13)
+                .unwrap()
+                .and_hms_opt(8, 30, 0)
+        );
+    }
+
+    #[test]
+    fn invalid_date() {
+        let params = pairs(&[("created_after", "yesterday")]);
+        assert!(extract_question_query(&params).is_err());
+    }
+
+    #[test]
+    fn dates_out_of_order() {
+        let params = pairs(&[
+            ("created_after", "2024-06-13"),
+            ("created_before", "2024-06-01"),
+        ]);
+        assert!(extract_question_query(&params).is_err());
+    }
+
+    #[test]
+    fn pagination_params() {
+        let params = pairs(&[("limit", "5"), ("offset", "10"), ("tag", "rust")]);
+        let query = extract_question_query(&params).unwrap();
+        assert_eq!(query.page.limit, 5);
+        assert_eq!(query.page.offset, 10);
+        assert!(query.tags.is_some());
+    }
+
+    #[test]
+    fn answer_sort() {
+        let mut params = HashMap::new();
+        assert_eq!(extract_answer_sort(&params).unwrap(), AnswerSort::Oldest);
+        params.insert(String::from("sort"), String::from("score"));
+        assert_eq!(extract_answer_sort(&params).unwrap(), AnswerSort::Score);
+        params.insert(String::from("sort"), String::from("newest"));
+        assert!(extract_answer_sort(&params).is_err());
+    }
+}
@@ -1,3 +1,7 @@
+use crate::types::answer::{Answer, AnswerId};
+use crate::types::moderation::ModerationStatus;
+
+use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 
 #[allow(clippy::module_name_repetitions)]
@@ -10,6 +14,10 @@ pub struct Question {
     pub title: String,
     pub content: String,
     pub tags: Option<Vec<String>>,
+    pub created_on: NaiveDateTime,
+    pub score: i32,
+    pub accepted_answer_id: Option<AnswerId>,
+    pub moderation_status: ModerationStatus,
 }
 
 #[allow(clippy::module_name_repetitions)]
@@ -19,3 +27,12 @@ pub struct NewQuestion {
     pub content: String,
     pub tags: Option<Vec<String>>,
 }
+
+/// A single question together with the answers posted to it
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct QuestionWithAnswers {
+    #[serde(flatten)]
+    pub question: Question,
+    pub answers: Vec<Answer>,
+}
@@ -0,0 +1,96 @@
+use crate::types::answer::AnswerId;
+use crate::types::pagination::{self, Pagination};
+use crate::types::question::QuestionId;
+use handle_errors::Error;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A single search hit, either on a question or on one of its answers
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SearchResult {
+    pub question_id: QuestionId,
+    /// Set when the hit is on an answer rather than the question itself
+    pub answer_id: Option<AnswerId>,
+    pub title: String,
+    /// Matching fragment of the content with the search terms wrapped in `<mark>` tags
+    pub snippet: String,
+    pub rank: f32,
+}
+
+/// Search terms and pagination extracted from query params
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SearchQuery {
+    pub terms: String,
+    pub pagination: Pagination,
+}
+
+/// Extract query parameters from the `/search` route
+///
+/// # Example query
+/// `/search?q=borrow+checker&limit=10&offset=0`
+///
+/// # Example usage
+/// ```rust
+/// use std::collections::HashMap;
+/// use eroteme::types::search;
+///
+/// let mut query = HashMap::new();
+/// query.insert("q".to_string(), "lifetimes".to_string());
+/// let s = search::extract_search(&query).unwrap();
+///
+/// assert_eq!(s.terms, "lifetimes");
+/// assert_eq!(s.pagination.limit, None);
+/// ```
+///
+/// # Errors
+///
+/// Will return `Err` if the `q` parameter is missing or empty, or the pagination parameters
+/// are invalid.
+pub fn extract_search<S: ::std::hash::BuildHasher>(
+    params: &HashMap<String, String, S>,
+) -> Result<SearchQuery, Error> {
+    let terms = match params.get("q").map(|q| q.trim()) {
+        Some(q) if !q.is_empty() => q.to_owned(),
+        _ => return Err(Error::MissingParameters),
+    };
+
+    let pagination = if params.contains_key("limit") || params.contains_key("offset") {
+        pagination::extract_pagination(params)?
+    } else {
+        Pagination::default()
+    };
+
+    Ok(SearchQuery { terms, pagination })
+}
+
+#[cfg(test)]
+mod search_tests {
+    use super::{extract_search, Error, HashMap, Pagination, SearchQuery};
+
+    #[test]
+    fn terms_with_pagination() {
+        let mut params = HashMap::new();
+        params.insert(String::from("q"), String::from(" async rust "));
+        params.insert(String::from("limit"), String::from("5"));
+        params.insert(String::from("offset"), String::from("10"));
+        let expected = SearchQuery {
+            terms: String::from("async rust"),
+            pagination: Pagination {
+                limit: Some(5),
+                offset: 10,
+            },
+        };
+        assert_eq!(extract_search(&params).unwrap(), expected);
+    }
+
+    #[test]
+    fn missing_terms() {
+        let mut params = HashMap::new();
+        params.insert(String::from("q"), String::from("  "));
+        let search_result = format!("{}", extract_search(&params).unwrap_err());
+        let expected = format!("{}", Error::MissingParameters);
+        assert_eq!(search_result, expected);
+    }
+}
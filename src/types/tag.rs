@@ -0,0 +1,178 @@
+use handle_errors::Error;
+use serde::{Deserialize, Serialize};
+
+/// Maximum number of tags a question can carry
+pub const MAX_TAGS: usize = 5;
+/// Maximum length of a single tag, in characters
+pub const MAX_TAG_LENGTH: usize = 32;
+
+/// A tag together with the number of questions using it
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Tag {
+    pub name: String,
+    pub count: i64,
+}
+
+/// Whether a question must carry any or all of the requested tags
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TagMode {
+    #[default]
+    Any,
+    All,
+}
+
+/// Tag filter that is getting extracted from query params
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TagFilter {
+    pub tags: Vec<String>,
+    pub mode: TagMode,
+}
+
+/// Lowercase and trim tags, dropping empty and duplicate entries
+///
+/// # Example usage
+/// ```rust
+/// use eroteme::types::tag;
+///
+/// let tags = vec![" Rust".to_string(), "ASYNC ".to_string(), "rust".to_string()];
+/// let tags = tag::normalize_tags(Some(tags)).unwrap();
+///
+/// assert_eq!(tags, Some(vec!["rust".to_string(), "async".to_string()]));
+/// ```
+///
+/// # Errors
+///
+/// Will return `Err` if a tag is longer than `MAX_TAG_LENGTH` or there are more than
+/// `MAX_TAGS` tags.
+pub fn normalize_tags(tags: Option<Vec<String>>) -> Result<Option<Vec<String>>, Error> {
+    let Some(tags) = tags else {
+        return Ok(None);
+    };
+
+    let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = normalize_tag(&tag)?;
+        if !tag.is_empty() && !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+
+    if normalized.len() > MAX_TAGS {
+        return Err(Error::InvalidTag(format!(
+            "a question can have at most {MAX_TAGS} tags"
+        )));
+    }
+
+    Ok(Some(normalized))
+}
+
+fn normalize_tag(tag: &str) -> Result<String, Error> {
+    let tag = tag.trim().to_lowercase();
+    if tag.chars().count() > MAX_TAG_LENGTH {
+        return Err(Error::InvalidTag(format!(
+            "`{tag}` is longer than {MAX_TAG_LENGTH} characters"
+        )));
+    }
+    Ok(tag)
+}
+
+/// Extract the tag filter from the `/questions` route
+///
+/// # Example query
+/// `/questions?tag=rust&tag=async&tag_mode=all`
+///
+/// # Errors
+///
+/// Will return `Err` if a tag is too long or `tag_mode` is neither `any` nor `all`.
+#[allow(clippy::module_name_repetitions)]
+pub fn extract_tag_filter(params: &[(String, String)]) -> Result<Option<TagFilter>, Error> {
+    let mut tags = Vec::new();
+    let mut mode = TagMode::default();
+
+    for (key, value) in params {
+        match key.as_str() {
+            "tag" => {
+                let tag = normalize_tag(value)?;
+                if !tag.is_empty() && !tags.contains(&tag) {
+                    tags.push(tag);
+                }
+            }
+            "tag_mode" => {
+                mode = match value.as_str() {
+                    "any" => TagMode::Any,
+                    "all" => TagMode::All,
+                    _ => {
+                        return Err(Error::InvalidParameter(format!(
+                            "tag_mode must be `any` or `all`, got `{value}`"
+                        )))
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if tags.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(TagFilter { tags, mode }))
+}
+
+#[cfg(test)]
+mod tag_tests {
+    use super::{extract_tag_filter, normalize_tags, TagFilter, TagMode, MAX_TAGS};
+
+    fn pairs(params: &[(&str, &str)]) -> Vec<(String, String)> {
+        params
+            .iter()
+            .map(|(k, v)| ((*k).to_owned(), (*v).to_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn too_many_tags() {
+        let tags = (0..=MAX_TAGS).map(|i| format!("tag{i}")).collect();
+        assert!(normalize_tags(Some(tags)).is_err());
+    }
+
+    #[test]
+    fn tag_too_long() {
+        let tags = vec!["a".repeat(33)];
+        let result = format!("{}", normalize_tags(Some(tags)).unwrap_err());
+        assert!(result.starts_with("invalid tag:"));
+    }
+
+    #[test]
+    fn empty_tags_are_dropped() {
+        let tags = vec!["  ".to_owned(), "Rust".to_owned()];
+        assert_eq!(
+            normalize_tags(Some(tags)).unwrap(),
+            Some(vec!["rust".to_owned()])
+        );
+    }
+
+    #[test]
+    fn filter_all_tags() {
+        let params = pairs(&[("tag", "Rust"), ("tag", "async"), ("tag_mode", "all")]);
+        let expected = TagFilter {
+            tags: vec!["rust".to_owned(), "async".to_owned()],
+            mode: TagMode::All,
+        };
+        assert_eq!(extract_tag_filter(&params).unwrap(), Some(expected));
+    }
+
+    #[test]
+    fn no_tag_filter() {
+        let params = pairs(&[("limit", "1"), ("offset", "1")]);
+        assert_eq!(extract_tag_filter(&params).unwrap(), None);
+    }
+
+    #[test]
+    fn invalid_tag_mode() {
+        let params = pairs(&[("tag", "rust"), ("tag_mode", "some")]);
+        assert!(extract_tag_filter(&params).is_err());
+    }
+}
@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+
+/// Direction of a vote, sent as `1` or `-1`
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(try_from = "i16", into = "i16")]
+pub enum VoteValue {
+    Up,
+    Down,
+}
+
+impl TryFrom<i16> for VoteValue {
+    type Error = String;
+
+    fn try_from(value: i16) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Self::Up),
+            -1 => Ok(Self::Down),
+            _ => Err(format!("vote must be 1 or -1, got {value}")),
+        }
+    }
+}
+
+impl From<VoteValue> for i16 {
+    fn from(value: VoteValue) -> Self {
+        match value {
+            VoteValue::Up => 1,
+            VoteValue::Down => -1,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Vote {
+    pub value: VoteValue,
+}
+
+/// Score of a question or answer after a vote was cast or retracted
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Score {
+    pub score: i32,
+}
+
+#[cfg(test)]
+mod vote_tests {
+    use super::{Vote, VoteValue};
+
+    #[test]
+    fn up_and_down_votes() {
+        let vote: Vote = serde_json::from_str(r#"{"value": -1}"#).unwrap();
+        assert_eq!(vote.value, VoteValue::Down);
+        let vote: Vote = serde_json::from_str(r#"{"value": 1}"#).unwrap();
+        assert_eq!(vote.value, VoteValue::Up);
+    }
+
+    #[test]
+    fn invalid_vote() {
+        assert!(serde_json::from_str::<Vote>(r#"{"value": 2}"#).is_err());
+    }
+}
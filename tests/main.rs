@@ -1,3 +1,9 @@
+use async_trait::async_trait;
+use eroteme::mailer::{Email, Mailer};
+use eroteme::profanity::{Censored, ProfanityFilter, WordlistFilter};
+use eroteme::store::Store;
+use eroteme::types::pagination::Cursor;
+use eroteme::types::{AccountId, ModerationQueue, ModerationStatus, ModerationTarget};
 use eroteme::{oneshot, store, Config};
 use futures_util::FutureExt;
 use reqwest::header;
@@ -5,6 +11,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::io::{self, Write};
 use std::process::Command;
+use std::sync::{Arc, Mutex};
 use std::{panic, process};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -27,9 +34,113 @@ struct QuestionAnswer {
     tags: Option<Vec<String>>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct Answer {
+    content: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct AnswerResponse {
+    id: i32,
+    content: String,
+    question_id: i32,
+    account_id: i32,
+    created_on: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct Tokens {
+    access_token: String,
+    refresh_token: String,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 struct Token(String);
 
+/// Refuses content containing `oversized` like the API refuses a post that is too long
+#[derive(Debug)]
+struct RefusingFilter;
+
+#[async_trait]
+impl ProfanityFilter for RefusingFilter {
+    async fn check_profanity(&self, content: String) -> Result<Censored, handle_errors::Error> {
+        if content.contains("oversized") {
+            return Err(handle_errors::Error::ClientError(
+                handle_errors::APILayerError {
+                    status: 413,
+                    message: "too large".to_owned(),
+                },
+            ));
+        }
+        Ok(Censored::clean(content))
+    }
+}
+
+/// Records the content it checks, taking a while like a call to the API does
+#[derive(Debug, Default)]
+struct CountingFilter(Mutex<Vec<String>>);
+
+#[async_trait]
+impl ProfanityFilter for CountingFilter {
+    async fn check_profanity(&self, content: String) -> Result<Censored, handle_errors::Error> {
+        self.0
+            .lock()
+            .expect("filter poisoned")
+            .push(content.clone());
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        Ok(Censored::clean(content))
+    }
+}
+
+/// Keeps every email sent, so tests can follow the links in them
+#[derive(Debug, Default)]
+struct Outbox(Mutex<Vec<Email>>);
+
+#[async_trait]
+impl Mailer for Outbox {
+    async fn send(&self, email: Email) -> Result<(), handle_errors::Error> {
+        self.0.lock().expect("outbox poisoned").push(email);
+        Ok(())
+    }
+}
+
+impl Outbox {
+    /// Token of the last verification link sent
+    fn verification_token(&self) -> String {
+        let outbox = self.0.lock().expect("outbox poisoned");
+        let body = &outbox.last().expect("no email sent").body;
+        body.split("verify?token=")
+            .nth(1)
+            .expect("no verification link")
+            .trim()
+            .to_owned()
+    }
+
+    /// Token of the last password reset email sent, waiting for it since the email goes out
+    /// after the reply
+    async fn reset_token(&self, to: &str) -> String {
+        for _ in 0..50 {
+            {
+                let outbox = self.0.lock().expect("outbox poisoned");
+                if let Some(email) = outbox
+                    .iter()
+                    .rev()
+                    .find(|email| email.to == to && email.subject == "Reset your password")
+                {
+                    return email
+                        .body
+                        .split("\n\n")
+                        .nth(1)
+                        .expect("no reset token")
+                        .to_owned();
+                }
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+        panic!("no reset email sent to {to}");
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<(), handle_errors::Error> {
     dotenv::dotenv().ok();
@@ -66,12 +177,16 @@ async fn main() -> Result<(), handle_errors::Error> {
         .write_all(&s.stderr)
         .expect("failed to write to stdout");
 
-    let store = store::setup(&config).await?;
+    let mut store = store::setup(&config).await?;
+    let outbox = Arc::new(Outbox::default());
+    store.mailer = outbox.clone();
+    // Checked against a known word, so tests don't depend on the configured filter
+    store.profanity_filter = Arc::new(WordlistFilter::new(["shitty"]));
 
-    let handler = oneshot(store).await;
+    let handler = oneshot(store.clone()).await;
 
     let user = User {
-        email: "test&email.com".to_owned(),
+        email: "test@email.com".to_owned(),
         password: "password".to_owned(),
     };
 
@@ -100,10 +215,193 @@ async fn main() -> Result<(), handle_errors::Error> {
         process::exit(1);
     }
 
+    print!("running resend_verification...");
+
+    if matches!(
+        panic::AssertUnwindSafe(resend_verification(&outbox, token.clone()))
+            .catch_unwind()
+            .await,
+        Ok(())
+    ) {
+        println!("ok");
+    } else {
+        let _ = handler.sender.send(1);
+        process::exit(1);
+    }
+
+    print!("running stale_verification_link...");
+
+    if matches!(
+        panic::AssertUnwindSafe(stale_verification_link(&outbox))
+            .catch_unwind()
+            .await,
+        Ok(())
+    ) {
+        println!("ok");
+    } else {
+        let _ = handler.sender.send(1);
+        process::exit(1);
+    }
+
+    print!("running change_role...");
+
+    if matches!(
+        panic::AssertUnwindSafe(change_role(&store, token.clone()))
+            .catch_unwind()
+            .await,
+        Ok(())
+    ) {
+        println!("ok");
+    } else {
+        let _ = handler.sender.send(1);
+        process::exit(1);
+    }
+
+    print!("running reset_password...");
+
+    if matches!(
+        panic::AssertUnwindSafe(reset_password(&store, &outbox))
+            .catch_unwind()
+            .await,
+        Ok(())
+    ) {
+        println!("ok");
+    } else {
+        let _ = handler.sender.send(1);
+        process::exit(1);
+    }
+
+    let question: QuestionAnswer;
+
     print!("running post_question...");
 
+    if let Ok(q) = panic::AssertUnwindSafe(post_question(token.clone()))
+        .catch_unwind()
+        .await
+    {
+        question = q;
+        println!("ok");
+    } else {
+        let _ = handler.sender.send(1);
+        process::exit(1);
+    }
+
+    print!("running post_answer...");
+
     if matches!(
-        panic::AssertUnwindSafe(post_question(token))
+        panic::AssertUnwindSafe(post_answer(token.clone(), question.id))
+            .catch_unwind()
+            .await,
+        Ok(())
+    ) {
+        println!("ok");
+    } else {
+        let _ = handler.sender.send(1);
+        process::exit(1);
+    }
+
+    print!("running comment_on_posts...");
+
+    if matches!(
+        panic::AssertUnwindSafe(comment_on_posts(&outbox, token.clone(), question.id))
+            .catch_unwind()
+            .await,
+        Ok(())
+    ) {
+        println!("ok");
+    } else {
+        let _ = handler.sender.send(1);
+        process::exit(1);
+    }
+
+    print!("running out_of_range_cursor...");
+
+    if matches!(
+        panic::AssertUnwindSafe(out_of_range_cursor())
+            .catch_unwind()
+            .await,
+        Ok(())
+    ) {
+        println!("ok");
+    } else {
+        let _ = handler.sender.send(1);
+        process::exit(1);
+    }
+
+    print!("running edit_hidden_question...");
+
+    if matches!(
+        panic::AssertUnwindSafe(edit_hidden_question(&store, token.clone(), question.id))
+            .catch_unwind()
+            .await,
+        Ok(())
+    ) {
+        println!("ok");
+    } else {
+        let _ = handler.sender.send(1);
+        process::exit(1);
+    }
+
+    print!("running comment_on_hidden_question...");
+
+    if matches!(
+        panic::AssertUnwindSafe(comment_on_hidden_question(token, question.id))
+            .catch_unwind()
+            .await,
+        Ok(())
+    ) {
+        println!("ok");
+    } else {
+        let _ = handler.sender.send(1);
+        process::exit(1);
+    }
+
+    print!("running recheck_past_failing_posts...");
+
+    if matches!(
+        panic::AssertUnwindSafe(recheck_past_failing_posts(&store))
+            .catch_unwind()
+            .await,
+        Ok(())
+    ) {
+        println!("ok");
+    } else {
+        let _ = handler.sender.send(1);
+        process::exit(1);
+    }
+
+    print!("running concurrent_rechecks...");
+
+    if matches!(
+        panic::AssertUnwindSafe(concurrent_rechecks(&store))
+            .catch_unwind()
+            .await,
+        Ok(())
+    ) {
+        println!("ok");
+    } else {
+        let _ = handler.sender.send(1);
+        process::exit(1);
+    }
+
+    print!("running page_moderation_queue...");
+
+    if matches!(
+        panic::AssertUnwindSafe(page_moderation_queue(&store))
+            .catch_unwind()
+            .await,
+        Ok(())
+    ) {
+        println!("ok");
+    } else {
+        let _ = handler.sender.send(1);
+        process::exit(1);
+    }
+
+    print!("running remove_answered_question...");
+
+    if matches!(
+        panic::AssertUnwindSafe(remove_answered_question(&store, question.id))
             .catch_unwind()
             .await,
         Ok(())
@@ -143,10 +441,11 @@ async fn login(user: User) -> Token {
         .await
         .expect("login failed");
 
-    res.json::<Token>().await.expect("login failed")
+    let tokens = res.json::<Tokens>().await.expect("login failed");
+    Token(tokens.access_token)
 }
 
-async fn post_question(token: Token) {
+async fn post_question(token: Token) -> QuestionAnswer {
     let q = Question {
         title: "First Question".to_owned(),
         content: "How can I test?".to_owned(),
@@ -165,4 +464,600 @@ async fn post_question(token: Token) {
         .expect("post_question failed");
 
     assert_eq!(res.title, q.title);
+
+    res
+}
+
+async fn post_answer(token: Token, question_id: i32) {
+    let a = Answer {
+        content: "Post an answer.".to_owned(),
+    };
+
+    let client = reqwest::Client::new();
+    let res = client
+        .post(format!(
+            "http://localhost:3030/questions/{question_id}/answers"
+        ))
+        .header(header::AUTHORIZATION, token.0)
+        .json(&a)
+        .send()
+        .await
+        .expect("post_answer failed");
+
+    assert_eq!(res.status(), reqwest::StatusCode::CREATED);
+
+    let location = res
+        .headers()
+        .get(header::LOCATION)
+        .expect("post_answer failed")
+        .to_str()
+        .expect("post_answer failed")
+        .to_owned();
+
+    let res = res
+        .json::<AnswerResponse>()
+        .await
+        .expect("post_answer failed");
+
+    assert_eq!(res.content, a.content);
+    assert_eq!(res.question_id, question_id);
+    assert_eq!(
+        location,
+        format!("/questions/{question_id}/answers/{}", res.id)
+    );
+}
+
+async fn edit_hidden_question(store: &Store, token: Token, question_id: i32) {
+    store
+        .clone()
+        .set_moderation_status(
+            ModerationTarget::Question(question_id),
+            ModerationStatus::Hidden,
+            &AccountId(1),
+        )
+        .await
+        .expect("edit_hidden_question failed");
+
+    let q = Question {
+        title: "First Question".to_owned(),
+        content: "How can I test an edit?".to_owned(),
+    };
+
+    let client = reqwest::Client::new();
+    let res = client
+        .put(format!("http://localhost:3030/questions/{question_id}"))
+        .header(header::AUTHORIZATION, token.0)
+        .json(&q)
+        .send()
+        .await
+        .expect("edit_hidden_question failed")
+        .json::<Value>()
+        .await
+        .expect("edit_hidden_question failed");
+
+    assert_eq!(res["content"], q.content);
+    assert_eq!(res["moderation_status"], "hidden");
+
+    let res = client
+        .get(format!("http://localhost:3030/questions/{question_id}"))
+        .send()
+        .await
+        .expect("edit_hidden_question failed");
+
+    assert_eq!(res.status(), reqwest::StatusCode::NOT_FOUND);
+}
+
+async fn remove_answered_question(store: &Store, question_id: i32) {
+    store
+        .clone()
+        .remove_post(ModerationTarget::Question(question_id), &AccountId(1))
+        .await
+        .expect("remove_answered_question failed");
+
+    let answers = sqlx::query("SELECT id FROM answers WHERE corresponding_question = $1")
+        .bind(question_id)
+        .fetch_all(&store.connection)
+        .await
+        .expect("remove_answered_question failed");
+
+    assert!(answers.is_empty());
+}
+
+async fn recheck_past_failing_posts(store: &Store) {
+    let mut store = store.clone();
+    store.profanity_filter = Arc::new(RefusingFilter);
+
+    let mut ids = Vec::new();
+    for content in ["An oversized question", "A fine question"] {
+        let id: i32 = sqlx::query_scalar(
+            "INSERT INTO questions (title, content, account_id, moderation_status)
+            VALUES ('Unchecked', $1, 1, 'unchecked')
+            RETURNING id",
+        )
+        .bind(content)
+        .fetch_one(&store.connection)
+        .await
+        .expect("recheck_past_failing_posts failed");
+        ids.push(id);
+    }
+
+    let checked = store
+        .recheck_questions(10)
+        .await
+        .expect("recheck_past_failing_posts failed");
+    assert_eq!(checked, 2);
+
+    let statuses: Vec<ModerationStatus> = sqlx::query_scalar(
+        "SELECT moderation_status FROM questions WHERE id = ANY($1) ORDER BY id",
+    )
+    .bind(&ids)
+    .fetch_all(&store.connection)
+    .await
+    .expect("recheck_past_failing_posts failed");
+    assert_eq!(
+        statuses,
+        [ModerationStatus::PendingReview, ModerationStatus::Approved]
+    );
+
+    let mut answer_ids = Vec::new();
+    for content in ["An oversized answer", "A fine answer"] {
+        let id: i32 = sqlx::query_scalar(
+            "INSERT INTO answers (content, corresponding_question, account_id, moderation_status)
+            VALUES ($1, $2, 1, 'unchecked')
+            RETURNING id",
+        )
+        .bind(content)
+        .bind(ids[1])
+        .fetch_one(&store.connection)
+        .await
+        .expect("recheck_past_failing_posts failed");
+        answer_ids.push(id);
+    }
+
+    let checked = store
+        .recheck_answers(10)
+        .await
+        .expect("recheck_past_failing_posts failed");
+    assert_eq!(checked, 2);
+
+    let statuses: Vec<ModerationStatus> =
+        sqlx::query_scalar("SELECT moderation_status FROM answers WHERE id = ANY($1) ORDER BY id")
+            .bind(&answer_ids)
+            .fetch_all(&store.connection)
+            .await
+            .expect("recheck_past_failing_posts failed");
+    assert_eq!(
+        statuses,
+        [ModerationStatus::PendingReview, ModerationStatus::Approved]
+    );
+}
+
+async fn resend_verification(outbox: &Outbox, token: Token) {
+    let client = reqwest::Client::new();
+    let res = client
+        .post("http://localhost:3030/verify/resend")
+        .header(header::AUTHORIZATION, token.0.clone())
+        .send()
+        .await
+        .expect("resend_verification failed");
+    assert_eq!(res.status(), reqwest::StatusCode::ACCEPTED);
+    assert_eq!(outbox.0.lock().expect("outbox poisoned").len(), 2);
+
+    let res = client
+        .get(format!(
+            "http://localhost:3030/verify?token={}",
+            outbox.verification_token()
+        ))
+        .send()
+        .await
+        .expect("resend_verification failed");
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+
+    let res = client
+        .post("http://localhost:3030/verify/resend")
+        .header(header::AUTHORIZATION, token.0)
+        .send()
+        .await
+        .expect("resend_verification failed")
+        .text()
+        .await
+        .expect("resend_verification failed");
+    assert_eq!(res, "email already verified");
+}
+
+/// A link sent to a deleted account doesn't verify a new account with the same address
+async fn stale_verification_link(outbox: &Outbox) {
+    let user = User {
+        email: "stale@email.com".to_owned(),
+        password: "password".to_owned(),
+    };
+    register_new_user(&user).await;
+    let stale = outbox.verification_token();
+    let token = login(user.clone()).await;
+
+    let client = reqwest::Client::new();
+    let res = client
+        .delete("http://localhost:3030/account")
+        .header(header::AUTHORIZATION, token.0)
+        .json(&serde_json::json!({ "password": user.password }))
+        .send()
+        .await
+        .expect("stale_verification_link failed");
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+
+    register_new_user(&user).await;
+    let res = client
+        .get(format!("http://localhost:3030/verify?token={stale}"))
+        .send()
+        .await
+        .expect("stale_verification_link failed");
+    assert_eq!(res.status(), reqwest::StatusCode::NOT_FOUND);
+
+    let res = client
+        .get(format!(
+            "http://localhost:3030/verify?token={}",
+            outbox.verification_token()
+        ))
+        .send()
+        .await
+        .expect("stale_verification_link failed");
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
+
+/// A reset token sets a new password once, and not after it expires
+async fn reset_password(store: &Store, outbox: &Outbox) {
+    let user = User {
+        email: "reset@email.com".to_owned(),
+        password: "password".to_owned(),
+    };
+    register_new_user(&user).await;
+
+    let client = reqwest::Client::new();
+    let res = client
+        .post("http://localhost:3030/password/forgot")
+        .json(&serde_json::json!({ "email": user.email }))
+        .send()
+        .await
+        .expect("reset_password failed");
+    assert_eq!(res.status(), reqwest::StatusCode::ACCEPTED);
+
+    let reset = serde_json::json!({
+        "token": outbox.reset_token(&user.email).await,
+        "password": "new password",
+    });
+    let res = client
+        .post("http://localhost:3030/password/reset")
+        .json(&reset)
+        .send()
+        .await
+        .expect("reset_password failed");
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+
+    let res = client
+        .post("http://localhost:3030/password/reset")
+        .json(&reset)
+        .send()
+        .await
+        .expect("reset_password failed");
+    assert_eq!(res.status(), reqwest::StatusCode::UNPROCESSABLE_ENTITY);
+
+    login(User {
+        email: user.email.clone(),
+        password: "new password".to_owned(),
+    })
+    .await;
+
+    // Tokens are only stored for emails with an account
+    assert!(!store
+        .add_password_reset("nobody@email.com", b"unknown", 1)
+        .await
+        .expect("reset_password failed"));
+
+    assert!(store
+        .add_password_reset(&user.email, b"expired", -1)
+        .await
+        .expect("reset_password failed"));
+    assert_eq!(
+        store
+            .reset_password(b"expired", "hash")
+            .await
+            .expect("reset_password failed"),
+        None
+    );
+
+    assert!(store
+        .add_password_reset(&user.email, b"once", 1)
+        .await
+        .expect("reset_password failed"));
+    assert!(store
+        .reset_password(b"once", "hash")
+        .await
+        .expect("reset_password failed")
+        .is_some());
+    assert_eq!(
+        store
+            .reset_password(b"once", "hash")
+            .await
+            .expect("reset_password failed"),
+        None
+    );
+}
+
+/// A changed role applies to tokens issued before the change
+async fn change_role(store: &Store, token: Token) {
+    let set_role = |role: &'static str| {
+        sqlx::query("UPDATE accounts SET role = $1::account_role WHERE email = 'test@email.com'")
+            .bind(role)
+            .execute(&store.connection)
+    };
+
+    let client = reqwest::Client::new();
+    set_role("moderator").await.expect("change_role failed");
+    let res = client
+        .get("http://localhost:3030/moderation/flags")
+        .header(header::AUTHORIZATION, token.0.clone())
+        .send()
+        .await
+        .expect("change_role failed");
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+
+    set_role("user").await.expect("change_role failed");
+    let res = client
+        .get("http://localhost:3030/moderation/flags")
+        .header(header::AUTHORIZATION, token.0)
+        .send()
+        .await
+        .expect("change_role failed");
+    assert_eq!(res.status(), reqwest::StatusCode::FORBIDDEN);
+}
+
+/// Pages of the moderation queue hold `limit` posts of either kind and follow on from each
+/// other
+async fn page_moderation_queue(store: &Store) {
+    let posts = |queue: ModerationQueue| {
+        let questions = queue.questions.into_iter().map(|q| (true, q.id.0));
+        let answers = queue.answers.into_iter().map(|a| (false, a.id.0));
+        questions.chain(answers).collect::<Vec<_>>()
+    };
+
+    let all = posts(
+        store
+            .clone()
+            .get_moderation_queue(None, 0)
+            .await
+            .expect("page_moderation_queue failed"),
+    );
+    assert!(all.iter().any(|(is_question, _)| *is_question));
+    assert!(all.iter().any(|(is_question, _)| !*is_question));
+
+    let mut paged = Vec::new();
+    for offset in 0..=i32::try_from(all.len()).expect("page_moderation_queue failed") {
+        let page = posts(
+            store
+                .clone()
+                .get_moderation_queue(Some(1), offset)
+                .await
+                .expect("page_moderation_queue failed"),
+        );
+        assert!(page.len() <= 1);
+        paged.extend(page);
+    }
+    paged.sort_unstable();
+    let mut all = all;
+    all.sort_unstable();
+    assert_eq!(paged, all);
+}
+
+/// A score cursor that doesn't fit a score is refused rather than read as 0
+async fn out_of_range_cursor() {
+    let cursor = Cursor {
+        key: Some(i64::MAX),
+        created_on: chrono::Utc::now().naive_utc(),
+        id: 1,
+    };
+
+    let client = reqwest::Client::new();
+    let res = client
+        .get(format!(
+            "http://localhost:3030/questions?sort=score&after={}",
+            cursor.encode()
+        ))
+        .send()
+        .await
+        .expect("out_of_range_cursor failed");
+    assert_eq!(res.status(), reqwest::StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(
+        res.text().await.expect("out_of_range_cursor failed"),
+        "invalid parameter: cursor score is out of range"
+    );
+}
+
+/// Rechecks running at the same time, as on several server instances, claim different
+/// posts, so each post is sent to the filter once
+async fn concurrent_rechecks(store: &Store) {
+    let filter = Arc::new(CountingFilter::default());
+    let mut store = store.clone();
+    store.profanity_filter = filter.clone();
+
+    for i in 0..6 {
+        sqlx::query(
+            "INSERT INTO questions (title, content, account_id, moderation_status)
+            VALUES ('Unchecked', $1, 1, 'unchecked')",
+        )
+        .bind(format!("Concurrent question {i}"))
+        .execute(&store.connection)
+        .await
+        .expect("concurrent_rechecks failed");
+    }
+
+    let (first, second) = tokio::join!(store.recheck_questions(4), store.recheck_questions(4));
+    let first = first.expect("concurrent_rechecks failed");
+    let second = second.expect("concurrent_rechecks failed");
+    assert_eq!(first + second, 6);
+
+    let mut checked: Vec<String> = filter
+        .0
+        .lock()
+        .expect("filter poisoned")
+        .iter()
+        .filter(|content| content.starts_with("Concurrent"))
+        .cloned()
+        .collect();
+    checked.sort_unstable();
+    let expected: Vec<String> = (0..6).map(|i| format!("Concurrent question {i}")).collect();
+    assert_eq!(checked, expected);
+}
+
+async fn comment_on_posts(outbox: &Outbox, token: Token, question_id: i32) {
+    let client = reqwest::Client::new();
+    let answer = client
+        .post(format!(
+            "http://localhost:3030/questions/{question_id}/answers"
+        ))
+        .header(header::AUTHORIZATION, token.0.clone())
+        .json(&Answer {
+            content: "An answer to comment on.".to_owned(),
+        })
+        .send()
+        .await
+        .expect("comment_on_posts failed")
+        .json::<AnswerResponse>()
+        .await
+        .expect("comment_on_posts failed");
+
+    // Comments on posts that don't exist
+    let res = client
+        .get("http://localhost:3030/questions/9999/comments")
+        .send()
+        .await
+        .expect("comment_on_posts failed");
+    assert_eq!(res.status(), reqwest::StatusCode::NOT_FOUND);
+
+    let res = client
+        .post("http://localhost:3030/answers/9999/comments")
+        .header(header::AUTHORIZATION, token.0.clone())
+        .json(&serde_json::json!({ "content": "Nothing to comment on" }))
+        .send()
+        .await
+        .expect("comment_on_posts failed");
+    assert_eq!(res.status(), reqwest::StatusCode::NOT_FOUND);
+
+    // Profanity is censored
+    let res = client
+        .post(format!(
+            "http://localhost:3030/questions/{question_id}/comments"
+        ))
+        .header(header::AUTHORIZATION, token.0.clone())
+        .json(&serde_json::json!({ "content": "A shitty comment" }))
+        .send()
+        .await
+        .expect("comment_on_posts failed");
+    assert_eq!(res.status(), reqwest::StatusCode::CREATED);
+    let comment = res.json::<Value>().await.expect("comment_on_posts failed");
+    assert_eq!(comment["content"], "A ****** comment");
+    let comment_id = comment["id"].as_i64().expect("comment_on_posts failed");
+
+    // A reply stays on the post of its parent
+    let res = client
+        .post(format!(
+            "http://localhost:3030/answers/{}/comments",
+            answer.id
+        ))
+        .header(header::AUTHORIZATION, token.0.clone())
+        .json(&serde_json::json!({ "content": "A reply", "parent_id": comment_id }))
+        .send()
+        .await
+        .expect("comment_on_posts failed");
+    assert_eq!(res.status(), reqwest::StatusCode::UNPROCESSABLE_ENTITY);
+
+    // Only the owner edits or deletes a comment
+    let other = User {
+        email: "commenter@email.com".to_owned(),
+        password: "password".to_owned(),
+    };
+    register_new_user(&other).await;
+    let res = client
+        .get(format!(
+            "http://localhost:3030/verify?token={}",
+            outbox.verification_token()
+        ))
+        .send()
+        .await
+        .expect("comment_on_posts failed");
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+    let other = login(other).await;
+
+    let edit = serde_json::json!({ "content": "An edited comment" });
+    let res = client
+        .put(format!("http://localhost:3030/comments/{comment_id}"))
+        .header(header::AUTHORIZATION, other.0.clone())
+        .json(&edit)
+        .send()
+        .await
+        .expect("comment_on_posts failed");
+    assert_eq!(res.status(), reqwest::StatusCode::UNAUTHORIZED);
+
+    let res = client
+        .delete(format!("http://localhost:3030/comments/{comment_id}"))
+        .header(header::AUTHORIZATION, other.0)
+        .send()
+        .await
+        .expect("comment_on_posts failed");
+    assert_eq!(res.status(), reqwest::StatusCode::UNAUTHORIZED);
+
+    let res = client
+        .put(format!("http://localhost:3030/comments/{comment_id}"))
+        .header(header::AUTHORIZATION, token.0.clone())
+        .json(&edit)
+        .send()
+        .await
+        .expect("comment_on_posts failed")
+        .json::<Value>()
+        .await
+        .expect("comment_on_posts failed");
+    assert_eq!(res["content"], "An edited comment");
+
+    let res = client
+        .delete(format!("http://localhost:3030/comments/{comment_id}"))
+        .header(header::AUTHORIZATION, token.0)
+        .send()
+        .await
+        .expect("comment_on_posts failed");
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+
+    let comments = client
+        .get(format!(
+            "http://localhost:3030/questions/{question_id}/comments"
+        ))
+        .send()
+        .await
+        .expect("comment_on_posts failed")
+        .json::<Vec<Value>>()
+        .await
+        .expect("comment_on_posts failed");
+    assert!(comments.is_empty());
+}
+
+/// Comments are hidden along with their question
+async fn comment_on_hidden_question(token: Token, question_id: i32) {
+    let client = reqwest::Client::new();
+    let res = client
+        .get(format!(
+            "http://localhost:3030/questions/{question_id}/comments"
+        ))
+        .send()
+        .await
+        .expect("comment_on_hidden_question failed");
+    assert_eq!(res.status(), reqwest::StatusCode::NOT_FOUND);
+
+    let res = client
+        .post(format!(
+            "http://localhost:3030/questions/{question_id}/comments"
+        ))
+        .header(header::AUTHORIZATION, token.0)
+        .json(&serde_json::json!({ "content": "A comment on a hidden question" }))
+        .send()
+        .await
+        .expect("comment_on_hidden_question failed");
+    assert_eq!(res.status(), reqwest::StatusCode::NOT_FOUND);
 }